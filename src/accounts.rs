@@ -0,0 +1,105 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use serde::{Serialize, Deserialize};
+use crate::{Error, Result};
+use crate::api::youtube::Channel;
+
+/// A Google identity logged in to before, by the name the user gave it
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Account {
+    /// Grants new access tokens without logging in again
+    pub refresh_token: String,
+    /// The channel playlists are created on, which is a brand account's channel if one was picked when logging in
+    pub channel: Channel
+}
+
+/// The Google accounts logged in to with a name, so conversions can pick one without logging in every time
+pub struct Accounts {
+    path: PathBuf,
+    /// Name to account
+    entries: BTreeMap<String, Account>
+}
+
+impl Accounts {
+    /// The default location of the accounts file
+    pub fn default_path() -> Result<PathBuf> {
+        let dir = dirs::data_dir().ok_or(Error::NoDirectory("data"))?;
+        Ok(dir.join(env!("CARGO_PKG_NAME")).join("accounts.json"))
+    }
+
+    /// Load the accounts stored at `path`. A missing file is treated as having no accounts
+    ///
+    /// ## Errors
+    /// - IO errors
+    /// - If the file is not a valid accounts file
+    pub fn load(path: &Path) -> Result<Self> {
+        let entries = if path.exists() {
+            serde_json::from_str(&fs::read_to_string(path)?)?
+        } else {
+            BTreeMap::new()
+        };
+
+        Ok(Self {
+            path: path.to_path_buf(),
+            entries
+        })
+    }
+
+    /// The account named `name`
+    pub fn get(&self, name: &str) -> Option<&Account> {
+        self.entries.get(name)
+    }
+
+    /// Every account, ordered by name
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &Account)> {
+        self.entries.iter().map(|(name, account)| (name.as_str(), account))
+    }
+
+    /// Store `account` under `name`, replacing any account of that name, and save the file
+    ///
+    /// ## Errors
+    /// - IO errors
+    pub fn set(&mut self, name: &str, account: Account) -> Result<()> {
+        self.entries.insert(name.to_string(), account);
+        self.save()
+    }
+
+    /// Forget the account named `name` and save the file. Returns whether there was such an account
+    ///
+    /// ## Errors
+    /// - IO errors
+    pub fn remove(&mut self, name: &str) -> Result<bool> {
+        if self.entries.remove(name).is_none() {
+            return Ok(false);
+        }
+
+        self.save()?;
+        Ok(true)
+    }
+
+    /// Write the accounts to the file. The refresh tokens grant full access to the accounts' channels,
+    /// so on unix only the user may read the file
+    fn save(&self) -> Result<()> {
+        if let Some(parent) = self.path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+
+        let mut options = fs::OpenOptions::new();
+        options.write(true).create(true).truncate(true);
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::{OpenOptionsExt, PermissionsExt};
+            options.mode(0o600);
+
+            // The mode only applies when the file is created
+            if self.path.exists() {
+                fs::set_permissions(&self.path, fs::Permissions::from_mode(0o600))?;
+            }
+        }
+
+        options.open(&self.path)?.write_all(serde_json::to_string_pretty(&self.entries)?.as_bytes())?;
+        Ok(())
+    }
+}
@@ -1,5 +1,25 @@
+/// The Spotify Web API
 pub mod spotify;
+/// The YouTube Data API and the YouTube Music search
 pub mod youtube;
+/// The internal API of the YouTube Music web client
+pub mod ytmusic;
+
+/// Apply the rate limits configured in `limits`
+pub fn set_rate_limits(limits: &crate::env::RateLimits) {
+    if let Some(l) = limits.spotify {
+        spotify::set_rate_limit(l);
+    }
+    if let Some(l) = limits.youtube {
+        youtube::set_rate_limit(l);
+    }
+    if let Some(l) = limits.search {
+        youtube::set_search_rate_limit(l);
+    }
+    if let Some(l) = limits.innertube {
+        ytmusic::set_rate_limit(l);
+    }
+}
 
 /// Call the provided function `f` as soon as the ratelimit it allows.
 /// This macro call blocks until the ratelimit bucket `bucket` permits the request
@@ -0,0 +1,89 @@
+use reqwest::blocking::Response;
+use serde::Deserialize;
+use log::debug;
+
+/// Errors returned by the Spotify Web API
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    /// The client ID and secret were refused
+    #[error("Spotify refused the client credentials: {0}")]
+    InvalidClient(String),
+    /// The access token is invalid or expired, or lacks the required scope
+    #[error("Not authorized: {0}")]
+    Unauthorized(String),
+    /// The playlist or track does not exist, or is not visible to the user
+    #[error("Not found: {0}")]
+    NotFound(String),
+    /// Too many requests in a short time
+    #[error("Spotify rate limit exceeded")]
+    RateLimitExceeded,
+    /// Any other error response
+    #[error("Spotify Web API error {status}: {message}")]
+    Api {
+        status: u16,
+        message: String
+    },
+    #[error(transparent)]
+    Http(#[from] reqwest::Error)
+}
+
+/// The body of Web API errors
+#[derive(Deserialize)]
+struct ErrorResponse {
+    error: ErrorBody
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum ErrorBody {
+    /// Returned by the Web API
+    Regular {
+        #[serde(default)]
+        message: String
+    },
+    /// Returned by the accounts service, e.g. `invalid_client`
+    Authentication(String)
+}
+
+#[derive(Deserialize)]
+struct AuthenticationErrorResponse {
+    #[serde(default)]
+    error_description: String
+}
+
+/// Turn an error response into an [Error], passing successful responses through
+pub fn check(res: Response) -> Result<Response, Error> {
+    let status = res.status();
+    if status.is_success() {
+        return Ok(res);
+    }
+
+    let body = res.text()?;
+    debug!("Got error response {}: {}", status, body);
+
+    let message = match serde_json::from_str::<ErrorResponse>(&body) {
+        Ok(ErrorResponse { error: ErrorBody::Regular { message } }) => message,
+        Ok(ErrorResponse { error: ErrorBody::Authentication(error) }) => {
+            let description = serde_json::from_str::<AuthenticationErrorResponse>(&body)
+                .map(|e| e.error_description)
+                .unwrap_or_default();
+
+            if error == "invalid_client" {
+                return Err(Error::InvalidClient(description));
+            }
+
+            format!("{}: {}", error, description)
+        },
+        Err(_) => body
+    };
+
+    Err(match status.as_u16() {
+        401 | 403 => Error::Unauthorized(message),
+        404 => Error::NotFound(message),
+        429 => Error::RateLimitExceeded,
+        status => Error::Api {
+            status,
+            message
+        }
+    })
+}
@@ -0,0 +1,400 @@
+use crate::env::Env;
+use crate::matcher::Candidate;
+use crate::service::{Playlist, PlaylistDetails, PlaylistTrack, Privacy};
+use serde::{Serialize, Deserialize};
+use log::debug;
+use std::num::NonZeroU32;
+use std::sync::{Arc, Mutex};
+use ratelimit_meter::{DirectRateLimiter, LeakyBucket};
+use crate::try_rl;
+
+mod error;
+
+pub use error::Error;
+
+lazy_static! {
+    static ref CLIENT: reqwest::blocking::Client = reqwest::blocking::Client::new();
+    static ref BUCKET: Arc<Mutex<DirectRateLimiter>> = Arc::new(Mutex::new(DirectRateLimiter::<LeakyBucket>::per_second(nonzero_ext::nonzero!(10u32))));
+}
+
+/// Make at most `per_second` requests per second from now on
+pub fn set_rate_limit(per_second: NonZeroU32) {
+    *BUCKET.lock().expect("Failed to lock RL Bucket mutex") = DirectRateLimiter::<LeakyBucket>::per_second(per_second);
+}
+
+#[derive(Deserialize)]
+struct LoginResponse {
+    #[serde(rename(serialize= "accessToken"))]
+    access_token: String
+}
+
+/// Get an application access token, which can read public data like playlists and the catalog, but nothing of any user
+pub fn get_login_token(env: &Env) -> Result<String, Error> {
+    debug!("Requesting Spotify login token");
+    let auth_string = format!("{}:{}", env.spotify_client_id, env.spotify_client_secret);
+    let auth_string = base64::encode(auth_string);
+    debug!("Using Authorization: {:?}", &auth_string);
+
+    let res = try_rl!(BUCKET, CLIENT
+        .post("https://accounts.spotify.com/api/token")
+        .body("grant_type=client_credentials")
+        .header("Content-Type", "application/x-www-form-urlencoded")
+        .header("Authorization", format!("Basic {}", auth_string))
+        .send()?);
+    let response: LoginResponse = error::check(res)?.json()?;
+
+    Ok(response.access_token)
+}
+
+#[derive(Deserialize)]
+struct TrackResponse {
+    id: Option<String>,
+    name: String,
+    artists: Vec<Artist>,
+    album: Option<Album>,
+    duration_ms: Option<u64>,
+    #[serde(default)]
+    external_ids: ExternalIds
+}
+
+#[derive(Deserialize)]
+struct Artist {
+    name: String
+}
+
+#[derive(Deserialize)]
+struct Album {
+    name: String
+}
+
+#[derive(Deserialize, Default)]
+struct ExternalIds {
+    isrc: Option<String>
+}
+
+#[derive(Deserialize)]
+struct PlaylistResponse {
+    name: String,
+    /// HTML escaped, may contain links
+    description: Option<String>,
+    external_urls: ExternalUrls
+}
+
+#[derive(Deserialize)]
+struct ExternalUrls {
+    spotify: String
+}
+
+#[derive(Deserialize)]
+struct PlaylistTracksResponse {
+    next: Option<String>,
+    items: Vec<PlaylistItems>
+}
+
+#[derive(Deserialize)]
+struct PlaylistItems {
+    /// `null` for tracks which are no longer available on Spotify
+    track: Option<TrackResponse>
+}
+
+impl From<TrackResponse> for PlaylistTrack {
+    fn from(t: TrackResponse) -> Self {
+        Self {
+            id: t.id,
+            name: t.name,
+            artists: t.artists.into_iter().map(|a| a.name).collect(),
+            album: t.album.map(|a| a.name),
+            isrc: t.external_ids.isrc,
+            duration_ms: t.duration_ms
+        }
+    }
+}
+
+/// Fetch a public playlist and all its tracks. Tracks which are no longer available on Spotify are left out
+pub fn get_playlist(env: &Env, id: &str) -> Result<Playlist, Error> {
+    let login_token = get_login_token(env)?;
+    let header = format!("Bearer {}", login_token);
+    debug!("Using Authorization header: {}", &header);
+
+    let res = try_rl!(BUCKET, CLIENT
+        .get(format!("https://api.spotify.com/v1/playlists/{}", id))
+        .header("Authorization", &header)
+        .send()?);
+
+    debug!("Got HTTP status {}", &res.status());
+    let response: PlaylistResponse = error::check(res)?.json()?;
+
+    let tracks = get_playlist_next(&format!("https://api.spotify.com/v1/playlists/{}/tracks?offset=0&limit=100", id), &header)?;
+    let tracks: Vec<_> = tracks.into_iter()
+        .map(PlaylistTrack::from)
+        .collect();
+
+    Ok(Playlist {
+        name: response.name,
+        description: response.description
+            .map(|d| html_to_text(&d))
+            .filter(|d| !d.is_empty()),
+        url: response.external_urls.spotify,
+        tracks
+    })
+}
+
+/// Strip tags and unescape entities from the HTML Spotify uses in playlist descriptions
+fn html_to_text(html: &str) -> String {
+    lazy_static! {
+        static ref TAG: regex::Regex = regex::Regex::new(r"<[^>]*>").expect("Invalid TAG regex");
+    }
+
+    TAG.replace_all(html, "")
+        .replace("&quot;", "\"")
+        .replace("&#x27;", "'")
+        .replace("&#x2F;", "/")
+        .replace("&#39;", "'")
+        .replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&amp;", "&")
+        .trim()
+        .to_string()
+}
+
+fn get_playlist_next(next: &str, auth_header_value: &str) -> Result<Vec<TrackResponse>, Error> {
+    let res = try_rl!(BUCKET, CLIENT
+        .get(next)
+        .header("Authorization", auth_header_value)
+        .send()?);
+    let resp: PlaylistTracksResponse = error::check(res)?.json()?;
+
+    let mut tracks: Vec<_> = resp.items
+        .into_iter()
+        .filter_map(|f| f.track)
+        .collect();
+
+    if let Some(ref next) = resp.next {
+        tracks.append(&mut get_playlist_next(next, auth_header_value)?);
+    }
+
+    Ok(tracks)
+}
+
+#[derive(Deserialize)]
+struct TracksResponse {
+    /// `null` for IDs which are not a track
+    tracks: Vec<Option<TrackResponse>>
+}
+
+/// Look up the tracks `ids`, [MAX_TRACKS_PER_LOOKUP] per request. `auth` is any access token.
+/// The result is in the order of `ids`, with `None` for IDs Spotify doesn't know
+pub fn get_tracks(ids: &[String], auth: &str) -> Result<Vec<Option<PlaylistTrack>>, Error> {
+    let mut tracks = Vec::with_capacity(ids.len());
+    for batch in ids.chunks(MAX_TRACKS_PER_LOOKUP) {
+        let res = try_rl!(BUCKET, CLIENT
+            .get("https://api.spotify.com/v1/tracks")
+            .query(&[("ids", batch.join(","))])
+            .header("Authorization", format!("Bearer {}", auth))
+            .send()?);
+
+        debug!("Looked up {} tracks, got status {}", batch.len(), res.status());
+        let res: TracksResponse = error::check(res)?.json()?;
+        tracks.extend(res.tracks.into_iter().map(|t| t.map(PlaylistTrack::from)));
+    }
+
+    Ok(tracks)
+}
+
+/// Maximum number of tracks which can be looked up in a single request
+pub const MAX_TRACKS_PER_LOOKUP: usize = 50;
+/// Maximum number of tracks which can be added to a playlist in a single request
+pub const MAX_TRACKS_PER_REQUEST: usize = 100;
+/// Maximum length of a playlist description in characters
+const MAX_DESCRIPTION_LENGTH: usize = 300;
+
+#[derive(Deserialize)]
+struct SearchResponse {
+    tracks: SearchTracks
+}
+
+#[derive(Deserialize)]
+struct SearchTracks {
+    items: Vec<TrackResponse>
+}
+
+/// Search the Spotify catalog for tracks matching `query`, which may use Spotify's field filters like `isrc:`.
+/// `auth` is any access token
+pub fn search(query: &str, auth: &str) -> Result<Vec<Candidate>, Error> {
+    let res = try_rl!(BUCKET, CLIENT
+        .get("https://api.spotify.com/v1/search")
+        .query(&[("q", query), ("type", "track"), ("limit", "10")])
+        .header("Authorization", format!("Bearer {}", auth))
+        .send()?);
+
+    debug!("Got search response status: {}", res.status());
+    let res: SearchResponse = error::check(res)?.json()?;
+    let candidates: Vec<_> = res.tracks.items
+        .into_iter()
+        .filter_map(|t| Some(Candidate {
+            id: t.id?,
+            title: t.name,
+            artists: t.artists.into_iter().map(|a| a.name).collect(),
+            album: t.album.map(|a| a.name),
+            duration_ms: t.duration_ms
+        }))
+        .collect();
+
+    debug!("Found {} candidates", candidates.len());
+    Ok(candidates)
+}
+
+#[derive(Deserialize)]
+struct CurrentUserResponse {
+    id: String
+}
+
+#[derive(Serialize)]
+struct CreatePlaylistRequest<'a> {
+    name: &'a str,
+    description: String,
+    public: bool
+}
+
+#[derive(Deserialize)]
+struct CreatePlaylistResponse {
+    id: String
+}
+
+/// Create a playlist owned by the logged in user, returning its ID. `auth` must be a user access token.
+/// Spotify has no unlisted playlists, so those are created private
+///
+/// ## Errors
+/// - [Error::Unauthorized] if the token is not a user token with the playlist scopes
+pub fn create_playlist(details: &PlaylistDetails, auth: &str) -> Result<String, Error> {
+    let header = format!("Bearer {}", auth);
+    let res = try_rl!(BUCKET, CLIENT
+        .get("https://api.spotify.com/v1/me")
+        .header("Authorization", &header)
+        .send()?);
+    let user: CurrentUserResponse = error::check(res)?.json()?;
+
+    // Spotify drops descriptions containing line breaks
+    let description = details.description
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .chars()
+        .take(MAX_DESCRIPTION_LENGTH)
+        .collect();
+
+    let req = CreatePlaylistRequest {
+        name: &details.title,
+        description,
+        public: matches!(details.privacy, Some(Privacy::Public) | None)
+    };
+
+    let res = try_rl!(BUCKET, CLIENT
+        .post(format!("https://api.spotify.com/v1/users/{}/playlists", user.id))
+        .header("Authorization", &header)
+        .json(&req)
+        .send()?);
+
+    debug!("Created playlist, got response code {}", res.status());
+    let res: CreatePlaylistResponse = error::check(res)?.json()?;
+    Ok(res.id)
+}
+
+#[derive(Deserialize)]
+struct PlaylistTrackIdsResponse {
+    next: Option<String>,
+    items: Vec<PlaylistTrackIdItem>
+}
+
+#[derive(Deserialize)]
+struct PlaylistTrackIdItem {
+    track: Option<TrackIdResponse>
+}
+
+#[derive(Deserialize)]
+struct TrackIdResponse {
+    /// `None` for local files
+    id: Option<String>
+}
+
+/// List the IDs of the tracks in a playlist, in playlist order. Items which are no longer available or are local files are `None`,
+/// so the index of every item is its position. `auth` must be a user access token if the playlist is private
+pub fn list_playlist_tracks(playlist_id: &str, auth: &str) -> Result<Vec<Option<String>>, Error> {
+    let mut ids = Vec::new();
+    let mut next = Some(format!("https://api.spotify.com/v1/playlists/{}/tracks?fields=next,items(track(id))&limit=100", playlist_id));
+
+    while let Some(url) = next {
+        let res = try_rl!(BUCKET, CLIENT
+            .get(&url)
+            .header("Authorization", format!("Bearer {}", auth))
+            .send()?);
+        let res: PlaylistTrackIdsResponse = error::check(res)?.json()?;
+
+        ids.extend(res.items.into_iter().map(|i| i.track.and_then(|t| t.id)));
+        next = res.next;
+    }
+
+    debug!("Playlist {} contains {} tracks", playlist_id, ids.len());
+    Ok(ids)
+}
+
+#[derive(Serialize)]
+struct AddTracksRequest {
+    uris: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    position: Option<usize>
+}
+
+/// Add up to [MAX_TRACKS_PER_REQUEST] tracks to a playlist in a single request, at the zero-based `position` or at the end if `None`.
+/// `auth` must be a user access token
+pub fn add_tracks(playlist_id: &str, track_ids: &[String], position: Option<usize>, auth: &str) -> Result<(), Error> {
+    let req = AddTracksRequest {
+        uris: track_ids.iter()
+            .map(|id| format!("spotify:track:{}", id))
+            .collect(),
+        position
+    };
+
+    let res = try_rl!(BUCKET, CLIENT
+        .post(format!("https://api.spotify.com/v1/playlists/{}/tracks", playlist_id))
+        .header("Authorization", format!("Bearer {}", auth))
+        .json(&req)
+        .send()?);
+
+    debug!("Added {} tracks to playlist {}, got status {}", track_ids.len(), playlist_id, res.status());
+    error::check(res)?;
+
+    Ok(())
+}
+
+#[derive(Serialize)]
+struct RemoveTracksRequest {
+    tracks: Vec<TrackUri>
+}
+
+#[derive(Serialize)]
+struct TrackUri {
+    uri: String
+}
+
+/// Remove every occurrence of up to [MAX_TRACKS_PER_REQUEST] tracks from a playlist in a single request. `auth` must be a user access token
+pub fn remove_tracks(playlist_id: &str, track_ids: &[String], auth: &str) -> Result<(), Error> {
+    let req = RemoveTracksRequest {
+        tracks: track_ids.iter()
+            .map(|id| TrackUri {
+                uri: format!("spotify:track:{}", id)
+            })
+            .collect()
+    };
+
+    let res = try_rl!(BUCKET, CLIENT
+        .delete(format!("https://api.spotify.com/v1/playlists/{}/tracks", playlist_id))
+        .header("Authorization", format!("Bearer {}", auth))
+        .json(&req)
+        .send()?);
+
+    debug!("Removed {} tracks from playlist {}, got status {}", track_ids.len(), playlist_id, res.status());
+    error::check(res)?;
+
+    Ok(())
+}
@@ -0,0 +1,103 @@
+use reqwest::blocking::Response;
+use serde::Deserialize;
+use log::debug;
+use crate::api::youtube::quota;
+
+/// Errors returned by the YouTube Data API
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    /// The daily quota of the Google Cloud project is used up. No further requests will succeed until it resets
+    #[error("YouTube Data API quota exceeded")]
+    QuotaExceeded,
+    /// Making the request would exceed the configured daily quota budget
+    #[error("The quota budget of {0} units for today is used up")]
+    QuotaBudgetExhausted(u64),
+    /// Too many requests in a short time
+    #[error("YouTube Data API rate limit exceeded")]
+    RateLimitExceeded,
+    #[error("Video not found")]
+    VideoNotFound,
+    #[error("Playlist not found")]
+    PlaylistNotFound,
+    /// The Google account has no YouTube channel to create playlists on
+    #[error("The account has no YouTube channel")]
+    ChannelNotFound,
+    /// The user is not allowed to access the playlist's items, e.g. because the playlist belongs to another channel
+    #[error("Playlist items not accessible")]
+    PlaylistItemsNotAccessible,
+    /// The playlist item to modify does not exist
+    #[error("Playlist item not found")]
+    PlaylistItemNotFound,
+    /// The access token is invalid or expired, or lacks the required scope
+    #[error("Not authorized: {0}")]
+    Unauthorized(String),
+    /// Any other error response
+    #[error("YouTube Data API error {status} ({reason}): {message}")]
+    Api {
+        status: u16,
+        reason: String,
+        message: String
+    },
+    #[error(transparent)]
+    Http(#[from] reqwest::Error)
+}
+
+impl Error {
+    /// Whether no further requests can succeed, so the caller should stop rather than continue with the next track
+    pub fn is_fatal(&self) -> bool {
+        matches!(self, Self::QuotaExceeded | Self::QuotaBudgetExhausted(_) | Self::Unauthorized(_))
+    }
+}
+
+/// The envelope Google APIs wrap errors in
+#[derive(Deserialize)]
+struct ErrorResponse {
+    error: ErrorBody
+}
+
+#[derive(Deserialize)]
+struct ErrorBody {
+    #[serde(default)]
+    message: String,
+    #[serde(default)]
+    errors: Vec<ErrorDetail>
+}
+
+#[derive(Deserialize)]
+struct ErrorDetail {
+    reason: String
+}
+
+/// Turn an error response into an [Error], passing successful responses through
+pub fn check(res: Response) -> Result<Response, Error> {
+    let status = res.status();
+    if status.is_success() {
+        return Ok(res);
+    }
+
+    let body = res.text()?;
+    debug!("Got error response {}: {}", status, body);
+
+    let (reason, message) = match serde_json::from_str::<ErrorResponse>(&body) {
+        Ok(e) => (e.error.errors.into_iter().next().map(|d| d.reason).unwrap_or_default(), e.error.message),
+        Err(_) => (String::new(), body)
+    };
+
+    Err(match reason.as_str() {
+        "quotaExceeded" | "dailyLimitExceeded" => {
+            quota::exhausted();
+            Error::QuotaExceeded
+        },
+        "rateLimitExceeded" | "userRateLimitExceeded" => Error::RateLimitExceeded,
+        "videoNotFound" => Error::VideoNotFound,
+        "playlistNotFound" => Error::PlaylistNotFound,
+        "playlistItemsNotAccessible" => Error::PlaylistItemsNotAccessible,
+        "playlistItemNotFound" => Error::PlaylistItemNotFound,
+        _ if status.as_u16() == 401 => Error::Unauthorized(message),
+        _ => Error::Api {
+            status: status.as_u16(),
+            reason,
+            message
+        }
+    })
+}
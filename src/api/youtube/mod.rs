@@ -0,0 +1,748 @@
+use serde::{Serialize, Deserialize};
+use log::debug;
+use std::num::NonZeroU32;
+use std::sync::{Arc, Mutex};
+use ratelimit_meter::{DirectRateLimiter, LeakyBucket};
+use crate::try_rl;
+use crate::matcher::Candidate;
+use crate::service::{PlaylistDetails, Privacy};
+
+mod error;
+/// Tracking of the Data API quota spent today
+pub mod quota;
+
+pub use error::Error;
+
+lazy_static! {
+    static ref CLIENT: reqwest::blocking::Client = reqwest::blocking::Client::new();
+    static ref BUCKET: Arc<Mutex<DirectRateLimiter>> = Arc::new(Mutex::new(DirectRateLimiter::<LeakyBucket>::per_second(nonzero_ext::nonzero!(10u32))));
+    static ref SEARCH_BUCKET: Arc<Mutex<DirectRateLimiter>> = Arc::new(Mutex::new(DirectRateLimiter::<LeakyBucket>::per_second(nonzero_ext::nonzero!(1u32))));
+}
+
+/// Make at most `per_second` requests per second to the Data API from now on
+pub fn set_rate_limit(per_second: NonZeroU32) {
+    *BUCKET.lock().expect("Failed to lock RL Bucket mutex") = DirectRateLimiter::<LeakyBucket>::per_second(per_second);
+}
+
+/// Make at most `per_second` YouTube Music searches per second from now on
+pub fn set_search_rate_limit(per_second: NonZeroU32) {
+    *SEARCH_BUCKET.lock().expect("Failed to lock RL Bucket mutex") = DirectRateLimiter::<LeakyBucket>::per_second(per_second);
+}
+
+#[derive(Serialize, Deserialize)]
+struct Playlist {
+    snippet: PlaylistSnippet,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    status: Option<PlaylistStatus>,
+    id: Option<String>
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct PlaylistSnippet {
+    title: String,
+    #[serde(default)]
+    description: String,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    tags: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    default_language: Option<String>
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct PlaylistStatus {
+    privacy_status: Privacy
+}
+
+/// Maximum length of a playlist title in characters
+const MAX_TITLE_LENGTH: usize = 150;
+/// Maximum length of a playlist description in bytes
+const MAX_DESCRIPTION_LENGTH: usize = 5000;
+
+/// YouTube rejects titles and descriptions containing angle brackets
+fn sanitize(s: &str) -> String {
+    s.replace('<', "‹").replace('>', "›")
+}
+
+/// Truncate `s` to at most `max` bytes without splitting a character
+fn truncate(mut s: String, max: usize) -> String {
+    if s.len() > max {
+        let mut end = max;
+        while !s.is_char_boundary(end) {
+            end -= 1;
+        }
+        s.truncate(end);
+    }
+
+    s
+}
+
+#[derive(Deserialize)]
+struct ChannelListResponse {
+    #[serde(default)]
+    items: Vec<ChannelResponse>
+}
+
+#[derive(Deserialize)]
+struct ChannelResponse {
+    id: String,
+    snippet: ChannelSnippet
+}
+
+#[derive(Deserialize)]
+struct ChannelSnippet {
+    title: String
+}
+
+/// A YouTube channel. Brand accounts have a channel of their own, distinct from the channel of the Google account managing them
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Channel {
+    pub id: String,
+    pub title: String
+}
+
+/// Get the channel `auth` was granted access to, which playlists are created on
+///
+/// ## Errors
+/// - [Error::ChannelNotFound] if the account has no channel
+/// - If the quota budget does not allow the request
+/// - When the request fails
+pub fn get_my_channel(auth: &str) -> Result<Channel, Error> {
+    quota::spend(quota::LIST_COST)?;
+    let res = try_rl!(BUCKET, CLIENT
+        .get("https://www.googleapis.com/youtube/v3/channels")
+        .query(&[("part", "snippet"), ("mine", "true")])
+        .header("Authorization", &format!("Bearer {}", auth))
+        .send()?);
+    let res: ChannelListResponse = error::check(res)?.json()?;
+
+    res.items.into_iter()
+        .next()
+        .map(|c| Channel {
+            id: c.id,
+            title: c.snippet.title
+        })
+        .ok_or(Error::ChannelNotFound)
+}
+
+/// Create a playlist on the user's channel, returning its ID
+///
+/// ## Errors
+/// - If the quota budget does not allow the request
+/// - When the request fails
+pub fn create_playlist(details: &PlaylistDetails, auth: &str) -> Result<String, Error> {
+    let req = Playlist {
+        snippet: PlaylistSnippet {
+            title: sanitize(&details.title).chars().take(MAX_TITLE_LENGTH).collect(),
+            description: truncate(sanitize(&details.description), MAX_DESCRIPTION_LENGTH),
+            tags: details.tags.clone(),
+            default_language: details.default_language.clone()
+        },
+        status: details.privacy.map(|privacy_status| PlaylistStatus {
+            privacy_status
+        }),
+        id: None
+    };
+
+    quota::spend(quota::WRITE_COST)?;
+    let res = try_rl!(BUCKET, CLIENT
+        .post("https://www.googleapis.com/youtube/v3/playlists?part=snippet,status")
+        .header("Authorization", &format!("Bearer {}", auth))
+        .json(&req)
+        .send()?);
+
+    let status = res.status();
+    debug!("Created playlist, got response code {}", status);
+    let res: Playlist = error::check(res)?.json()?;
+
+    res.id.ok_or_else(|| Error::Api {
+        status: status.as_u16(),
+        reason: String::new(),
+        message: "the created playlist has no ID".to_string()
+    })
+}
+
+#[derive(Serialize)]
+struct PlaylistItem {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    id: Option<String>,
+    snippet: PlaylistItemSnippet
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct PlaylistItemSnippet {
+    playlist_id: String,
+    resource_id: ResourceId,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    position: Option<usize>
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ResourceId {
+    kind: &'static str,
+    video_id: String
+}
+
+/// Insert a video into a playlist at the zero-based `position`, or at the end if `position` is `None`
+pub fn insert_track(playlist_id: &str, resource_id: &str, position: Option<usize>, auth: &str) -> Result<(), Error> {
+    let req = PlaylistItem {
+        id: None,
+        snippet: PlaylistItemSnippet {
+            resource_id: ResourceId {
+                kind: "youtube#video",
+                video_id: resource_id.to_string()
+            },
+            playlist_id: playlist_id.to_string(),
+            position
+        }
+    };
+
+    quota::spend(quota::WRITE_COST)?;
+    let res = try_rl!(BUCKET, CLIENT
+        .post("https://www.googleapis.com/youtube/v3/playlistItems?part=snippet")
+        .header("Authorization", &format!("Bearer {}", auth))
+        .json(&req)
+        .send()?);
+
+    debug!("Inserted item into playlist, got status: {}", res.status());
+    error::check(res)?;
+
+    Ok(())
+}
+
+/// Move an existing playlist item to the zero-based `position`
+pub fn move_playlist_item(playlist_id: &str, item_id: &str, video_id: &str, position: usize, auth: &str) -> Result<(), Error> {
+    let req = PlaylistItem {
+        id: Some(item_id.to_string()),
+        snippet: PlaylistItemSnippet {
+            resource_id: ResourceId {
+                kind: "youtube#video",
+                video_id: video_id.to_string()
+            },
+            playlist_id: playlist_id.to_string(),
+            position: Some(position)
+        }
+    };
+
+    quota::spend(quota::WRITE_COST)?;
+    let res = try_rl!(BUCKET, CLIENT
+        .put("https://www.googleapis.com/youtube/v3/playlistItems?part=snippet")
+        .header("Authorization", &format!("Bearer {}", auth))
+        .json(&req)
+        .send()?);
+
+    debug!("Moved playlist item, got status: {}", res.status());
+    error::check(res)?;
+
+    Ok(())
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct PlaylistItemsResponse {
+    next_page_token: Option<String>,
+    items: Vec<PlaylistItemResponse>
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct PlaylistItemResponse {
+    id: String,
+    snippet: PlaylistItemResponseSnippet
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct PlaylistItemResponseSnippet {
+    #[serde(default)]
+    title: String,
+    /// Absent for videos which are deleted or private
+    video_owner_channel_title: Option<String>,
+    resource_id: ResourceIdResponse
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ResourceIdResponse {
+    /// Absent for resources which aren't videos
+    video_id: Option<String>
+}
+
+/// An item in a YouTube playlist
+#[derive(Debug, Clone)]
+pub struct PlaylistEntry {
+    /// The ID of the playlist item, which is distinct from the video ID
+    pub item_id: String,
+    pub video_id: String
+}
+
+/// List all items in a playlist, in playlist order
+pub fn list_playlist_items(playlist_id: &str, auth: &str) -> Result<Vec<PlaylistEntry>, Error> {
+    let entries: Vec<_> = list_items(playlist_id, auth)?
+        .into_iter()
+        .filter_map(|i| Some(PlaylistEntry {
+            video_id: i.snippet.resource_id.video_id?,
+            item_id: i.id
+        }))
+        .collect();
+
+    debug!("Playlist {} contains {} items", playlist_id, entries.len());
+    Ok(entries)
+}
+
+fn list_items(playlist_id: &str, auth: &str) -> Result<Vec<PlaylistItemResponse>, Error> {
+    let mut items = Vec::new();
+    let mut page_token: Option<String> = None;
+
+    loop {
+        let mut query = vec![("part", "snippet"), ("maxResults", "50"), ("playlistId", playlist_id)];
+        if let Some(token) = &page_token {
+            query.push(("pageToken", token));
+        }
+
+        quota::spend(quota::LIST_COST)?;
+        let res = try_rl!(BUCKET, CLIENT
+            .get("https://www.googleapis.com/youtube/v3/playlistItems")
+            .query(&query)
+            .header("Authorization", &format!("Bearer {}", auth))
+            .send()?);
+        let res: PlaylistItemsResponse = error::check(res)?.json()?;
+
+        items.extend(res.items);
+        match res.next_page_token {
+            Some(token) => page_token = Some(token),
+            None => break
+        }
+    }
+
+    Ok(items)
+}
+
+#[derive(Deserialize)]
+struct PlaylistListResponse {
+    items: Vec<Playlist>
+}
+
+#[derive(Deserialize)]
+struct VideoListResponse {
+    items: Vec<Video>
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Video {
+    id: String,
+    content_details: VideoContentDetails
+}
+
+#[derive(Deserialize)]
+struct VideoContentDetails {
+    /// ISO 8601, e.g. `PT3M45S`
+    duration: String
+}
+
+/// Fetch a playlist and its videos as tracks, to convert it to another service.
+/// Title and artist of every track are taken from the video's metadata, deleted and private videos are left out
+///
+/// ## Errors
+/// - [Error::PlaylistNotFound] if the playlist doesn't exist or isn't visible to the user
+pub fn get_playlist(playlist_id: &str, auth: &str) -> Result<crate::service::Playlist, Error> {
+    quota::spend(quota::LIST_COST)?;
+    let res = try_rl!(BUCKET, CLIENT
+        .get("https://www.googleapis.com/youtube/v3/playlists")
+        .query(&[("part", "snippet"), ("id", playlist_id)])
+        .header("Authorization", &format!("Bearer {}", auth))
+        .send()?);
+    let res: PlaylistListResponse = error::check(res)?.json()?;
+    let playlist = res.items.into_iter().next().ok_or(Error::PlaylistNotFound)?;
+
+    let items: Vec<_> = list_items(playlist_id, auth)?
+        .into_iter()
+        .filter_map(|i| {
+            let channel = i.snippet.video_owner_channel_title?;
+            Some((i.snippet.resource_id.video_id?, i.snippet.title, channel))
+        })
+        .collect();
+
+    let video_ids: Vec<String> = items.iter().map(|(id, _, _)| id.clone()).collect();
+    let durations = get_durations(&video_ids, auth)?;
+
+    let tracks = items.into_iter()
+        .map(|(video_id, title, channel)| {
+            let (name, artist) = parse_track(&title, &channel);
+            crate::service::PlaylistTrack {
+                id: None,
+                name,
+                artists: vec![artist],
+                album: None,
+                isrc: None,
+                duration_ms: durations.get(&video_id).copied()
+            }
+        })
+        .collect();
+
+    Ok(crate::service::Playlist {
+        tracks,
+        name: playlist.snippet.title,
+        description: Some(playlist.snippet.description).filter(|d| !d.trim().is_empty()),
+        url: format!("https://music.youtube.com/playlist?list={}", playlist_id)
+    })
+}
+
+/// Look up the duration of every video in `video_ids`, in milliseconds
+fn get_durations(video_ids: &[String], auth: &str) -> Result<std::collections::HashMap<String, u64>, Error> {
+    let mut durations = std::collections::HashMap::new();
+    for chunk in video_ids.chunks(50) {
+        quota::spend(quota::LIST_COST)?;
+        let res = try_rl!(BUCKET, CLIENT
+            .get("https://www.googleapis.com/youtube/v3/videos")
+            .query(&[("part", "contentDetails"), ("id", &chunk.join(","))])
+            .header("Authorization", &format!("Bearer {}", auth))
+            .send()?);
+        let res: VideoListResponse = error::check(res)?.json()?;
+
+        durations.extend(res.items
+            .into_iter()
+            .filter_map(|v| Some((v.id, parse_iso_duration(&v.content_details.duration)?))));
+    }
+
+    Ok(durations)
+}
+
+/// Parse an ISO 8601 duration like `PT1H2M3S` into milliseconds
+fn parse_iso_duration(text: &str) -> Option<u64> {
+    let time = text.strip_prefix("PT")?;
+    let mut secs = 0;
+    let mut number = String::new();
+    for c in time.chars() {
+        match c {
+            '0'..='9' => number.push(c),
+            'H' | 'M' | 'S' => {
+                let n: u64 = std::mem::take(&mut number).parse().ok()?;
+                secs += n * match c {
+                    'H' => 3600,
+                    'M' => 60,
+                    _ => 1
+                };
+            },
+            _ => return None
+        }
+    }
+
+    Some(secs * 1000)
+}
+
+/// Split the metadata of a video into track title and artist.
+/// Auto-generated `Artist - Topic` channels upload tracks under their bare title,
+/// other uploads are usually titled `Artist - Title (Official Video)`
+fn parse_track(title: &str, channel: &str) -> (String, String) {
+    lazy_static! {
+        static ref NOISE: regex::Regex = regex::Regex::new(r"(?i)\s*[(\[](?:official\s*)?(?:music\s*)?(?:video|audio|lyrics?(?:\s*video)?|visuali[sz]er|hd|hq|mv|m/v)[)\]]").expect("Invalid NOISE regex");
+    }
+
+    if let Some(artist) = channel.strip_suffix(" - Topic") {
+        return (title.to_string(), artist.to_string());
+    }
+
+    let title = NOISE.replace_all(title, "");
+    match title.split_once(" - ") {
+        Some((artist, name)) => (name.trim().to_string(), artist.trim().to_string()),
+        None => (title.trim().to_string(), channel.trim_end_matches("VEVO").trim().to_string())
+    }
+}
+
+/// Remove the playlist item `item_id`, which is the ID of the item rather than of its video
+///
+/// ## Errors
+/// - If the quota budget does not allow the request
+/// - When the request fails
+pub fn delete_playlist_item(item_id: &str, auth: &str) -> Result<(), Error> {
+    quota::spend(quota::WRITE_COST)?;
+    let res = try_rl!(BUCKET, CLIENT
+        .delete("https://www.googleapis.com/youtube/v3/playlistItems")
+        .query(&[("id", item_id)])
+        .header("Authorization", &format!("Bearer {}", auth))
+        .send()?);
+
+    debug!("Deleted item from playlist, got status: {}", res.status());
+    error::check(res)?;
+
+    Ok(())
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Search {
+    contents: Contents
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Contents {
+    tabbed_search_results_renderer: TabbedSearchResultsRenderer
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct TabbedSearchResultsRenderer {
+    tabs: Vec<Tab>
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Tab {
+    tab_renderer: TabRenderer
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct TabRenderer {
+    content: Content1
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Content1 {
+    section_list_renderer: SectionListRenderer
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SectionListRenderer {
+    contents: Vec<Content2>
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Content2 {
+    /// `None` for shelves which aren't a list of results, e.g. the 'top result' card
+    music_shelf_renderer: Option<MusicShelfRenderer>
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct MusicShelfRenderer {
+    contents: Vec<Content3>
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Content3 {
+    music_responsive_list_item_renderer: Option<MusicResponsiveListItemRenderer>
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct MusicResponsiveListItemRenderer {
+    flex_columns: Vec<FlexColumn>
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct FlexColumn {
+    music_responsive_list_item_flex_column_renderer: MusicResponsiveListItemFlexColumnRenderer
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct MusicResponsiveListItemFlexColumnRenderer {
+    #[serde(default)]
+    text: Text
+}
+
+#[derive(Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+struct Text {
+    #[serde(default)]
+    runs: Vec<Run>
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Run {
+    text: String,
+    navigation_endpoint: Option<NavigationEndpoint>
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct NavigationEndpoint {
+    watch_endpoint: Option<WatchEndpoint>,
+    browse_endpoint: Option<BrowseEndpoint>
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct WatchEndpoint {
+    video_id: String
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct BrowseEndpoint {
+    browse_endpoint_context_supported_configs: Option<BrowseEndpointContextSupportedConfigs>
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct BrowseEndpointContextSupportedConfigs {
+    browse_endpoint_context_music_config: BrowseEndpointContextMusicConfig
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct BrowseEndpointContextMusicConfig {
+    page_type: String
+}
+
+impl Run {
+    /// The YouTube Music page type this run links to, e.g. `MUSIC_PAGE_TYPE_ARTIST`
+    fn page_type(&self) -> Option<&str> {
+        Some(self.navigation_endpoint.as_ref()?
+            .browse_endpoint.as_ref()?
+            .browse_endpoint_context_supported_configs.as_ref()?
+            .browse_endpoint_context_music_config
+            .page_type
+            .as_str())
+    }
+}
+
+/// Search YouTube Music for `terms`, returning all results in the order YouTube Music ranked them
+pub fn search(terms: &str) -> crate::Result<Vec<Candidate>> {
+    let res = try_rl!(SEARCH_BUCKET, CLIENT
+        .get("https://music.youtube.com/search")
+        .query(&[("q", terms)])
+        .header("User-Agent", "Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/87.0.4280.88 Safari/537.36 Edg/87.0.664.66")
+        .send()?);
+
+    debug!("Got search response status: {}", res.status());
+    let res = res.text()?;
+
+    log::trace!("Got response: {}", &res);
+
+    debug!("Extracting JSON from HTML response");
+    let data = res
+        .split("initialData.push({path: '\\/search',")
+        .nth(1)
+        .ok_or_else(|| crate::Error::Search("missing 1st data element".to_string()))?
+        .split("), data: '")
+        .nth(1)
+        .ok_or_else(|| crate::Error::Search("missing 2nd data element".to_string()))?
+        .split("'});ytcfg.set({'YTMUSIC_INITIAL_DATA'")
+        .next()
+        .ok_or_else(|| crate::Error::Search("missing 3rd data element".to_string()))?
+        .to_string();
+
+    debug!("Unescaping Unicode encoding");
+    let unicode = data
+        .replace(r#"\x"#, r#"\u00"#)
+        .replace(r#"\u0022"#, r#"""#)
+        .replace(r#"\u003d"#, "=")
+        .replace(r#"\u005d"#, "]")
+        .replace(r#"\u005b"#, "[")
+        .replace(r#"\u007b"#, "{")
+        .replace(r#"\u007d"#, "}")
+        .replace(r#"\\"#, r#"\"#);
+
+    debug!("Deserializing JSON");
+    log::trace!("{}", &unicode);
+    let data: Search = match serde_json::from_str(&unicode) {
+        Ok(d) => d,
+        Err(e) => {
+            log::warn!("Failed to deserialize JSON: {:?}", e);
+            log::debug!("Received JSON: {}", &unicode);
+            return Err(e.into())
+        }
+    };
+
+    let candidates = get_candidates(data.contents);
+    debug!("Found {} candidates", candidates.len());
+
+    Ok(candidates)
+}
+
+fn get_candidates(contents: Contents) -> Vec<Candidate> {
+    let tab = match contents.tabbed_search_results_renderer.tabs.into_iter().next() {
+        Some(t) => t,
+        None => return Vec::new()
+    };
+
+    tab.tab_renderer
+        .content
+        .section_list_renderer
+        .contents
+        .into_iter()
+        .filter_map(|c| c.music_shelf_renderer)
+        .flat_map(|shelf| shelf.contents)
+        .filter_map(|c| c.music_responsive_list_item_renderer)
+        .filter_map(get_candidate)
+        .collect()
+}
+
+/// Labels YouTube Music prefixes a result's details with when searching without a filter
+const RESULT_TYPE_LABELS: &[&str] = &["Song", "Video", "Album", "Single", "EP", "Artist", "Playlist", "Episode", "Podcast"];
+
+fn get_candidate(item: MusicResponsiveListItemRenderer) -> Option<Candidate> {
+    let mut columns = item.flex_columns
+        .into_iter()
+        .map(|fc| fc.music_responsive_list_item_flex_column_renderer.text.runs);
+
+    let title_runs = columns.next()?;
+    let detail_runs = columns.next().unwrap_or_default();
+
+    // Results without a watch endpoint are artists, albums or playlists, which we can't insert
+    let video_id = title_runs.iter()
+        .chain(detail_runs.iter())
+        .filter_map(|r| r.navigation_endpoint.as_ref()?.watch_endpoint.as_ref())
+        .map(|we| we.video_id.clone())
+        .next()?;
+
+    let title = title_runs.iter()
+        .map(|r| r.text.as_str())
+        .collect::<String>();
+
+    let mut artists = Vec::new();
+    let mut album = None;
+    let mut duration_ms = None;
+    let mut unlinked = Vec::new();
+
+    for r in detail_runs.iter().filter(|r| r.text != " • ") {
+        match r.page_type() {
+            Some("MUSIC_PAGE_TYPE_ARTIST") | Some("MUSIC_PAGE_TYPE_USER_CHANNEL") => artists.push(r.text.clone()),
+            Some("MUSIC_PAGE_TYPE_ALBUM") => album = Some(r.text.clone()),
+            _ => match parse_duration(&r.text) {
+                Some(d) => duration_ms = Some(d),
+                None => unlinked.push(r.text.as_str())
+            }
+        }
+    }
+
+    // Not every artist has a channel to link to, in which case the artist is the first plain text segment
+    if artists.is_empty() {
+        if let Some(a) = unlinked.into_iter().find(|t| !RESULT_TYPE_LABELS.contains(t)) {
+            artists.push(a.to_string());
+        }
+    }
+
+    Some(Candidate {
+        id: video_id,
+        title,
+        artists,
+        album,
+        duration_ms
+    })
+}
+
+/// Parse a duration formatted like `3:45` or `1:02:03` into milliseconds
+fn parse_duration(text: &str) -> Option<u64> {
+    if !text.contains(':') {
+        return None;
+    }
+
+    text.split(':')
+        .try_fold(0u64, |acc, part| Some(acc * 60 + part.parse::<u64>().ok()?))
+        .map(|secs| secs * 1000)
+}
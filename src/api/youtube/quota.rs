@@ -0,0 +1,134 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+use log::{debug, warn};
+use serde::{Serialize, Deserialize};
+use crate::api::youtube::Error;
+
+/// Quota cost of a `list` request
+pub const LIST_COST: u64 = 1;
+/// Quota cost of an `insert`, `update` or `delete` request
+pub const WRITE_COST: u64 = 50;
+
+/// The quota resets at midnight Pacific Time. We use standard time year-round, so during daylight saving time our day ends an hour late
+const PACIFIC_OFFSET_SECS: u64 = 8 * 60 * 60;
+
+lazy_static! {
+    static ref USAGE: Mutex<Option<Usage>> = Mutex::new(None);
+}
+
+/// Estimated quota usage for a single day, persisted across runs
+#[derive(Serialize, Deserialize)]
+struct Usage {
+    #[serde(skip)]
+    path: PathBuf,
+    #[serde(skip)]
+    budget: u64,
+    /// Days since the Unix epoch, Pacific Time
+    day: u64,
+    used: u64
+}
+
+impl Usage {
+    fn save(&self) {
+        let result = self.path.parent()
+            .map(fs::create_dir_all)
+            .transpose()
+            .and_then(|_| fs::write(&self.path, serde_json::to_string(self).unwrap_or_default()));
+
+        if let Err(e) = result {
+            warn!("Failed to save quota usage: {}", e);
+        }
+    }
+}
+
+/// The default location of the quota usage file
+pub fn default_path() -> crate::Result<PathBuf> {
+    let dir = dirs::data_dir().ok_or(crate::Error::NoDirectory("data"))?;
+    Ok(dir.join(env!("CARGO_PKG_NAME")).join("quota.json"))
+}
+
+/// Start tracking quota usage against a daily `budget`, loading today's usage so far from `path`.
+/// Until this is called, usage is not tracked and requests are never refused
+///
+/// ## Errors
+/// - IO errors
+/// - If the file is not a valid quota usage file
+pub fn init(path: &Path, budget: u64) -> crate::Result<()> {
+    let today = today();
+    let mut usage = if path.exists() {
+        serde_json::from_str(&fs::read_to_string(path)?)?
+    } else {
+        Usage {
+            path: PathBuf::new(),
+            budget: 0,
+            day: today,
+            used: 0
+        }
+    };
+
+    if usage.day != today {
+        debug!("Quota has reset since the last run");
+        usage.day = today;
+        usage.used = 0;
+    }
+
+    usage.path = path.to_path_buf();
+    usage.budget = budget;
+    debug!("Used {} of {} quota units today", usage.used, budget);
+
+    *USAGE.lock().expect("Failed to lock quota usage mutex") = Some(usage);
+    Ok(())
+}
+
+/// Quota units left in today's budget, or `None` if usage is not tracked
+pub fn remaining() -> Option<u64> {
+    USAGE.lock()
+        .expect("Failed to lock quota usage mutex")
+        .as_ref()
+        .map(|u| u.budget.saturating_sub(u.used))
+}
+
+/// Record a request costing `cost` units, before it is made.
+///
+/// ## Errors
+/// - [Error::QuotaBudgetExhausted] if the request would exceed the budget, in which case it must not be made
+pub(super) fn spend(cost: u64) -> Result<(), Error> {
+    let mut guard = USAGE.lock().expect("Failed to lock quota usage mutex");
+    let usage = match guard.as_mut() {
+        Some(u) => u,
+        None => return Ok(())
+    };
+
+    if usage.day != today() {
+        usage.day = today();
+        usage.used = 0;
+    }
+
+    if usage.used + cost > usage.budget {
+        return Err(Error::QuotaBudgetExhausted(usage.budget));
+    }
+
+    usage.used += cost;
+    usage.save();
+    Ok(())
+}
+
+/// Record that YouTube reported the quota as exceeded, so later runs today don't try again
+pub(super) fn exhausted() {
+    let mut guard = USAGE.lock().expect("Failed to lock quota usage mutex");
+    if let Some(usage) = guard.as_mut() {
+        usage.used = usage.used.max(usage.budget);
+        usage.save();
+    }
+}
+
+fn today() -> u64 {
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default();
+
+    now.saturating_sub(PACIFIC_OFFSET_SECS) / (24 * 60 * 60)
+}
@@ -0,0 +1,185 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+use crate::{Error, Result};
+use serde::{Serialize, Deserialize};
+use serde_json::json;
+use log::debug;
+use std::num::NonZeroU32;
+use std::sync::{Arc, Mutex};
+use ratelimit_meter::{DirectRateLimiter, LeakyBucket};
+use crate::try_rl;
+use crate::service::{PlaylistDetails, Privacy};
+
+lazy_static! {
+    static ref CLIENT: reqwest::blocking::Client = reqwest::blocking::Client::new();
+    static ref BUCKET: Arc<Mutex<DirectRateLimiter>> = Arc::new(Mutex::new(DirectRateLimiter::<LeakyBucket>::per_second(nonzero_ext::nonzero!(2u32))));
+}
+
+/// Make at most `per_second` requests per second from now on
+pub fn set_rate_limit(per_second: NonZeroU32) {
+    *BUCKET.lock().expect("Failed to lock RL Bucket mutex") = DirectRateLimiter::<LeakyBucket>::per_second(per_second);
+}
+
+const ORIGIN: &str = "https://music.youtube.com";
+const CLIENT_NAME: &str = "WEB_REMIX";
+const CLIENT_VERSION: &str = "1.20230102.01.00";
+
+/// A logged in YouTube Music browser session, used to edit playlists through the internal API of the web client (InnerTube).
+/// Unlike the Data API, InnerTube is not subject to a quota
+pub struct Session {
+    /// The full `Cookie` header of the session
+    cookie: String,
+    /// The value of the `SAPISID` cookie, which requests are signed with
+    sapisid: String
+}
+
+impl Session {
+    /// Create a session from the `Cookie` header of a request made by a logged in music.youtube.com tab
+    ///
+    /// ## Errors
+    /// - If the cookies don't contain `SAPISID`, i.e. the browser was not logged in
+    pub fn from_cookie(cookie: &str) -> Result<Self> {
+        let sapisid = cookie.split(';')
+            .filter_map(|c| c.trim().split_once('='))
+            .find(|(name, _)| *name == "SAPISID" || *name == "__Secure-3PAPISID")
+            .map(|(_, value)| value.to_string())
+            .ok_or_else(|| Error::YouTubeMusic("the cookie does not contain SAPISID, make sure it was copied from a logged in session".to_string()))?;
+
+        Ok(Self {
+            cookie: cookie.trim().to_string(),
+            sapisid
+        })
+    }
+
+    /// The `Authorization` header value: a SHA-1 hash of the current time, the SAPISID and the origin
+    fn authorization(&self) -> String {
+        use sha1::digest::Digest;
+
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or_default();
+
+        let mut hasher = sha1::Sha1::new();
+        hasher.update(format!("{} {} {}", timestamp, self.sapisid, ORIGIN).as_bytes());
+        let hash = hasher.finalize()
+            .iter()
+            .map(|b| format!("{:02x}", b))
+            .collect::<String>();
+
+        format!("SAPISIDHASH {}_{}", timestamp, hash)
+    }
+
+    fn post<T: Serialize>(&self, endpoint: &str, body: &T) -> Result<reqwest::blocking::Response> {
+        let res = try_rl!(BUCKET, CLIENT
+            .post(format!("{}/youtubei/v1/{}", ORIGIN, endpoint))
+            .query(&[("alt", "json"), ("prettyPrint", "false")])
+            .header("Cookie", &self.cookie)
+            .header("Authorization", self.authorization())
+            .header("Origin", ORIGIN)
+            .header("X-Goog-AuthUser", "0")
+            .header("User-Agent", "Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/87.0.4280.88 Safari/537.36 Edg/87.0.664.66")
+            .json(body)
+            .send()?);
+
+        debug!("Got InnerTube {} response status: {}", endpoint, res.status());
+        if !res.status().is_success() {
+            return Err(Error::YouTubeMusic(format!("got {} for {}: {}", res.status(), endpoint, res.text()?)));
+        }
+
+        Ok(res)
+    }
+}
+
+/// The client InnerTube requests claim to come from
+fn context() -> serde_json::Value {
+    json!({
+        "client": {
+            "clientName": CLIENT_NAME,
+            "clientVersion": CLIENT_VERSION,
+            "hl": "en"
+        }
+    })
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct CreatePlaylistRequest {
+    context: serde_json::Value,
+    title: String,
+    description: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    privacy_status: Option<&'static str>
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct CreatePlaylistResponse {
+    playlist_id: String
+}
+
+/// Create a playlist, returning its ID
+pub fn create_playlist(session: &Session, details: &PlaylistDetails) -> Result<String> {
+    let req = CreatePlaylistRequest {
+        context: context(),
+        title: details.title.clone(),
+        description: details.description.clone(),
+        privacy_status: details.privacy.map(|p| match p {
+            Privacy::Public => "PUBLIC",
+            Privacy::Unlisted => "UNLISTED",
+            Privacy::Private => "PRIVATE"
+        })
+    };
+
+    let res: CreatePlaylistResponse = session.post("playlist/create", &req)?.json()?;
+    debug!("Created playlist {}", res.playlist_id);
+    Ok(res.playlist_id)
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct EditPlaylistRequest<'a> {
+    context: serde_json::Value,
+    playlist_id: &'a str,
+    actions: Vec<EditAction<'a>>
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct EditAction<'a> {
+    action: &'static str,
+    added_video_id: &'a str,
+    /// `DEDUPE_OPTION_SKIP` to not add videos which are in the playlist already
+    #[serde(skip_serializing_if = "Option::is_none")]
+    dedupe_option: Option<&'static str>
+}
+
+#[derive(Deserialize)]
+struct EditPlaylistResponse {
+    status: String
+}
+
+/// Append all `video_ids` to a playlist in a single request. With `skip_existing`, videos which are in the playlist already,
+/// including earlier ones of the same request, are silently left out
+pub fn add_tracks(session: &Session, playlist_id: &str, video_ids: &[String], skip_existing: bool) -> Result<()> {
+    let req = EditPlaylistRequest {
+        context: context(),
+        // The web client refers to playlists with a 'VL' prefix, the edit endpoint wants them without
+        playlist_id: playlist_id.strip_prefix("VL").unwrap_or(playlist_id),
+        actions: video_ids.iter()
+            .map(|id| EditAction {
+                action: "ACTION_ADD_VIDEO",
+                added_video_id: id,
+                dedupe_option: skip_existing.then_some("DEDUPE_OPTION_SKIP")
+            })
+            .collect()
+    };
+
+    let res: EditPlaylistResponse = session.post("browse/edit_playlist", &req)?.json()?;
+    debug!("Added {} tracks to playlist {}, got status {}", video_ids.len(), playlist_id, res.status);
+
+    if res.status != "STATUS_SUCCEEDED" {
+        return Err(Error::YouTubeMusic(format!("the tracks were not added, status: {}", res.status)));
+    }
+
+    Ok(())
+}
@@ -0,0 +1,283 @@
+use std::collections::HashMap;
+use std::fs::{self, File, OpenOptions};
+use std::io::{BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use crate::{Error, Result};
+use log::{debug, warn};
+use serde::{Serialize, Deserialize};
+use crate::service::PlaylistTrack;
+use crate::matcher::Match;
+
+/// A cached match, stored as one JSON object per line
+#[derive(Serialize, Deserialize, Clone)]
+struct CacheEntry {
+    spotify_id: Option<String>,
+    isrc: Option<String>,
+    #[serde(rename = "match")]
+    found: Match,
+    /// Seconds since the Unix epoch at which the match was made
+    timestamp: u64
+}
+
+/// On-disk cache of Spotify track to YouTube video matches, keyed by both Spotify track ID and ISRC
+pub struct MatchCache {
+    path: PathBuf,
+    entries: HashMap<String, CacheEntry>,
+    ttl: Duration,
+    /// Ignore existing entries, but still record new ones
+    refresh: bool
+}
+
+impl MatchCache {
+    /// The default location of the cache file
+    pub fn default_path() -> Result<PathBuf> {
+        let dir = dirs::cache_dir().ok_or(Error::NoDirectory("cache"))?;
+        Ok(dir.join(env!("CARGO_PKG_NAME")).join("matches.jsonl"))
+    }
+
+    /// Open the cache stored at `path`, creating it if it does not exist.
+    /// Entries older than `ttl` are dropped, and the file is compacted if it contains any dropped or superseded entries
+    ///
+    /// ## Errors
+    /// - IO errors
+    pub fn open(path: &Path, ttl: Duration, refresh: bool) -> Result<Self> {
+        let mut cache = Self {
+            path: path.to_path_buf(),
+            entries: HashMap::new(),
+            ttl,
+            refresh
+        };
+
+        if !path.exists() {
+            return Ok(cache);
+        }
+
+        let mut lines = 0;
+        for line in BufReader::new(File::open(path)?).lines() {
+            let line = line?;
+            lines += 1;
+
+            match serde_json::from_str::<CacheEntry>(&line) {
+                Ok(entry) if !cache.is_expired(&entry) => cache.index(entry),
+                Ok(_) => {},
+                Err(e) => warn!("Ignoring invalid cache entry: {:?}", e)
+            }
+        }
+
+        if lines > cache.unique_entries().len() {
+            debug!("Compacting match cache");
+            cache.compact()?;
+        }
+
+        debug!("Loaded {} cached matches", cache.unique_entries().len());
+        Ok(cache)
+    }
+
+    /// Get the cached match for `track`, if there is one
+    pub fn get(&self, track: &PlaylistTrack) -> Option<&Match> {
+        if self.refresh {
+            return None;
+        }
+
+        keys(track)
+            .iter()
+            .find_map(|k| self.entries.get(k))
+            .map(|e| &e.found)
+    }
+
+    /// Record `found` as the match for `track`.
+    /// Tracks without a Spotify ID or ISRC, i.e. local files, are not cached
+    ///
+    /// ## Errors
+    /// - IO errors
+    pub fn insert(&mut self, track: &PlaylistTrack, found: &Match) -> Result<()> {
+        if keys(track).is_empty() {
+            return Ok(());
+        }
+
+        let entry = CacheEntry {
+            spotify_id: track.id.clone(),
+            isrc: track.isrc.clone(),
+            found: found.clone(),
+            timestamp: now()
+        };
+
+        let mut file = self.open_file(OpenOptions::new().create(true).append(true))?;
+        writeln!(file, "{}", serde_json::to_string(&entry)?)?;
+
+        self.index(entry);
+        Ok(())
+    }
+
+    /// Number of cached matches
+    pub fn len(&self) -> usize {
+        self.unique_entries().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Drop every cached match
+    ///
+    /// ## Errors
+    /// - IO errors
+    pub fn clear(&mut self) -> Result<()> {
+        self.entries.clear();
+        self.compact()
+    }
+
+    fn index(&mut self, entry: CacheEntry) {
+        for key in entry_keys(entry.spotify_id.as_deref(), entry.isrc.as_deref()) {
+            self.entries.insert(key, entry.clone());
+        }
+    }
+
+    fn is_expired(&self, entry: &CacheEntry) -> bool {
+        now().saturating_sub(entry.timestamp) > self.ttl.as_secs()
+    }
+
+    /// Every entry once, as entries are indexed under multiple keys
+    fn unique_entries(&self) -> Vec<&CacheEntry> {
+        let mut seen = std::collections::HashSet::new();
+        self.entries.values()
+            .filter(|e| seen.insert((&e.spotify_id, &e.isrc)))
+            .collect()
+    }
+
+    fn compact(&self) -> Result<()> {
+        let mut file = self.open_file(OpenOptions::new().create(true).write(true).truncate(true))?;
+        for entry in self.unique_entries() {
+            writeln!(file, "{}", serde_json::to_string(entry)?)?;
+        }
+
+        Ok(())
+    }
+
+    fn open_file(&self, options: &OpenOptions) -> Result<File> {
+        if let Some(parent) = self.path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+
+        Ok(options.open(&self.path)?)
+    }
+}
+
+fn keys(track: &PlaylistTrack) -> Vec<String> {
+    entry_keys(track.id.as_deref(), track.isrc.as_deref())
+}
+
+fn entry_keys(spotify_id: Option<&str>, isrc: Option<&str>) -> Vec<String> {
+    spotify_id.map(|id| format!("spotify:{}", id))
+        .into_iter()
+        .chain(isrc.map(|isrc| format!("isrc:{}", isrc)))
+        .collect()
+}
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DAY: Duration = Duration::from_secs(24 * 60 * 60);
+
+    /// A cache file path of its own for every test, removed once the test is done
+    struct TempPath(PathBuf);
+
+    impl TempPath {
+        fn new(name: &str) -> Self {
+            let path = std::env::temp_dir().join(format!("{}-{}-{}.jsonl", env!("CARGO_PKG_NAME"), name, std::process::id()));
+            let _ = fs::remove_file(&path);
+            Self(path)
+        }
+    }
+
+    impl Drop for TempPath {
+        fn drop(&mut self) {
+            let _ = fs::remove_file(&self.0);
+        }
+    }
+
+    fn track(id: Option<&str>, isrc: Option<&str>) -> PlaylistTrack {
+        PlaylistTrack {
+            id: id.map(str::to_string),
+            name: "September".to_string(),
+            artists: vec!["Earth, Wind & Fire".to_string()],
+            album: None,
+            isrc: isrc.map(str::to_string),
+            duration_ms: None
+        }
+    }
+
+    fn entry(spotify_id: &str, video_id: &str, timestamp: u64) -> String {
+        serde_json::to_string(&CacheEntry {
+            spotify_id: Some(spotify_id.to_string()),
+            isrc: None,
+            found: Match::manual(video_id),
+            timestamp
+        }).unwrap()
+    }
+
+    fn cached<'a>(cache: &'a MatchCache, t: &PlaylistTrack) -> Option<&'a str> {
+        cache.get(t).map(|m| m.candidate.id.as_str())
+    }
+
+    #[test]
+    fn lookup_by_id_and_isrc() {
+        let path = TempPath::new("lookup");
+        let mut cache = MatchCache::open(&path.0, DAY, false).unwrap();
+        cache.insert(&track(Some("a"), Some("USABC1234567")), &Match::manual("video")).unwrap();
+        cache.insert(&track(None, None), &Match::manual("local")).unwrap();
+
+        // Either key finds the match, also after reopening
+        let cache = MatchCache::open(&path.0, DAY, false).unwrap();
+        assert_eq!(cache.len(), 1);
+        assert_eq!(cached(&cache, &track(Some("a"), None)), Some("video"));
+        assert_eq!(cached(&cache, &track(Some("other"), Some("USABC1234567"))), Some("video"));
+        assert_eq!(cached(&cache, &track(Some("b"), None)), None);
+    }
+
+    #[test]
+    fn expiry_and_compaction() {
+        let path = TempPath::new("expiry");
+        let lines = [
+            entry("old", "expired", now() - 2 * DAY.as_secs()),
+            entry("a", "superseded", now() - 60),
+            entry("a", "latest", now()),
+            "not json".to_string(),
+            entry("b", "kept", now())
+        ];
+        fs::write(&path.0, lines.join("\n") + "\n").unwrap();
+
+        let cache = MatchCache::open(&path.0, DAY, false).unwrap();
+        assert_eq!(cache.len(), 2);
+        assert_eq!(cached(&cache, &track(Some("old"), None)), None);
+        assert_eq!(cached(&cache, &track(Some("a"), None)), Some("latest"));
+        assert_eq!(cached(&cache, &track(Some("b"), None)), Some("kept"));
+
+        // Only the entries still in use are written back
+        assert_eq!(fs::read_to_string(&path.0).unwrap().lines().count(), 2);
+    }
+
+    #[test]
+    fn refresh() {
+        let path = TempPath::new("refresh");
+        fs::write(&path.0, entry("a", "old", now()) + "\n").unwrap();
+
+        // Existing entries are ignored, but new matches are still recorded
+        let mut cache = MatchCache::open(&path.0, DAY, true).unwrap();
+        assert_eq!(cached(&cache, &track(Some("a"), None)), None);
+        cache.insert(&track(Some("a"), None), &Match::manual("new")).unwrap();
+        assert_eq!(cached(&cache, &track(Some("a"), None)), None);
+
+        let cache = MatchCache::open(&path.0, DAY, false).unwrap();
+        assert_eq!(cached(&cache, &track(Some("a"), None)), Some("new"));
+    }
+}
@@ -1,32 +1,310 @@
-use clap::{App, Arg};
+use clap::{App, AppSettings, Arg, Shell, SubCommand};
 
 pub fn clap() -> App<'static, 'static> {
     App::new(env!("CARGO_PKG_NAME"))
         .version(env!("CARGO_PKG_VERSION"))
         .author(env!("CARGO_PKG_AUTHORS"))
+        .about("Convert playlists between Spotify and YouTube (Music)")
+        .setting(AppSettings::SubcommandRequiredElseHelp)
+        .setting(AppSettings::VersionlessSubcommands)
+        .arg(Arg::with_name("config")
+            .long("config")
+            .help("Path of the config file. Defaults to config.toml in the user's config directory")
+            .takes_value(true)
+            .global(true))
+        .arg(Arg::with_name("profile")
+            .long("profile")
+            .help("Profile of the config file to use. Defaults to the file's 'default_profile'")
+            .takes_value(true)
+            .global(true))
+        .arg(Arg::with_name("account")
+            .long("account")
+            .help("Name of the stored Google account to use. An account not stored yet is logged in to and stored under this name. Defaults to the account a resumed or synced conversion used, then to the config file's")
+            .takes_value(true)
+            .global(true))
         .arg(Arg::with_name("google-client-id")
             .short("a")
             .long("google-client-id")
-            .required(true)
-            .takes_value(true))
+            .help("Needed to log in to Google, which dry runs from Spotify don't do. Overrides the config file")
+            .takes_value(true)
+            .global(true))
         .arg(Arg::with_name("google-client-secret")
             .short("b")
             .long("google-client-secret")
-            .required(true)
-            .takes_value(true))
+            .help("Needed to log in to Google, which dry runs from Spotify don't do. Overrides the config file")
+            .takes_value(true)
+            .global(true))
         .arg(Arg::with_name("spotify-client-id")
             .short("c")
             .long("spotify-client-id")
-            .required(true)
-            .takes_value(true))
+            .help("Needed for everything reading from or writing to Spotify. Overrides the config file")
+            .takes_value(true)
+            .global(true))
         .arg(Arg::with_name("spotify-client-secret")
             .short("d")
             .long("spotify-client-secret")
-            .required(true)
-            .takes_value(true))
-        .arg(Arg::with_name("playlist-url")
-            .short("p")
-            .long("playlist-url")
-            .required(true)
-            .takes_value(true))
-}
\ No newline at end of file
+            .help("Needed for everything reading from or writing to Spotify. Overrides the config file")
+            .takes_value(true)
+            .global(true))
+        .subcommand(SubCommand::with_name("convert")
+            .about("Convert a playlist into a new playlist of the other service")
+            .arg(playlist_arg())
+            .args(&new_job_args())
+            .args(&target_args())
+            .args(&match_args())
+            .args(&cache_args()))
+        .subcommand(SubCommand::with_name("sync")
+            .about("Bring the playlist this playlist was converted to before in line with it, or convert it if it never was")
+            .arg(playlist_arg())
+            .arg(Arg::with_name("remove-missing")
+                .long("remove-missing")
+                .help("Remove tracks from the target playlist which are not in the source playlist, or which no video was found for"))
+            .args(&new_job_args())
+            .args(&target_args())
+            .args(&match_args())
+            .args(&cache_args()))
+        .subcommand(SubCommand::with_name("resume")
+            .about("Resume an interrupted conversion")
+            .arg(Arg::with_name("job")
+                .help("Path of the job file of the conversion")
+                .required(true)
+                .index(1))
+            .args(&target_args())
+            .args(&match_args())
+            .args(&cache_args()))
+        .subcommand(SubCommand::with_name("export")
+            .about("Export a Spotify playlist to a file, without converting it")
+            .arg(Arg::with_name("playlist-url")
+                .help("The Spotify playlist to export")
+                .required(true)
+                .index(1))
+            .arg(Arg::with_name("output")
+                .help("The file to write. The format is picked by the extension: .csv, .json, .m3u8 or .xspf")
+                .required(true)
+                .index(2)))
+        .subcommand(SubCommand::with_name("resolve")
+            .about("Print the YouTube Music link of Spotify tracks, one line per track. The line is empty if no match was found")
+            .arg(Arg::with_name("tracks")
+                .help("Spotify track URLs, URIs or IDs. Read from stdin, one per line, if none are given")
+                .multiple(true)
+                .index(1))
+            .args(&match_args())
+            .args(&cache_args()))
+        .subcommand(SubCommand::with_name("auth")
+            .about("Log in to a service and print the access token. Logging in to Google with --account stores the account")
+            .arg(Arg::with_name("service")
+                .help("The service to log in to")
+                .required(true)
+                .possible_values(&["google", "spotify"])
+                .index(1))
+            .arg(quota_budget_arg())
+            .arg(redirect_port_arg()))
+        .subcommand(SubCommand::with_name("accounts")
+            .about("Manage the stored Google accounts")
+            .setting(AppSettings::SubcommandRequiredElseHelp)
+            .subcommand(SubCommand::with_name("list")
+                .about("Print the name and YouTube channel of every stored account"))
+            .subcommand(SubCommand::with_name("remove")
+                .about("Forget a stored account")
+                .arg(Arg::with_name("name")
+                    .required(true)
+                    .index(1))))
+        .subcommand(SubCommand::with_name("cache")
+            .about("Inspect or clear the match cache")
+            .setting(AppSettings::SubcommandRequiredElseHelp)
+            .args(&cache_file_args())
+            .subcommand(SubCommand::with_name("path")
+                .about("Print the location of the cache file"))
+            .subcommand(SubCommand::with_name("stats")
+                .about("Print the number of cached matches"))
+            .subcommand(SubCommand::with_name("prune")
+                .about("Drop expired matches from the cache file"))
+            .subcommand(SubCommand::with_name("clear")
+                .about("Drop every cached match")))
+        .subcommand(SubCommand::with_name("report")
+            .about("Write a report on the conversion saved in a job file")
+            .arg(Arg::with_name("job")
+                .help("Path of the job file of the conversion")
+                .required(true)
+                .index(1))
+            .arg(Arg::with_name("output")
+                .help("Files to write. The format follows the extension: .json, .csv or .html")
+                .required(true)
+                .multiple(true)
+                .index(2)))
+        .subcommand(SubCommand::with_name("completions")
+            .about("Print a shell completion script")
+            .arg(Arg::with_name("shell")
+                .required(true)
+                .possible_values(&Shell::variants())
+                .index(1)))
+}
+
+fn playlist_arg() -> Arg<'static, 'static> {
+    Arg::with_name("playlist-url")
+        .help("The playlist to convert. A Spotify playlist or a playlist file (.csv, .json, .m3u, .m3u8, .xspf) is converted to YouTube, a YouTube (Music) playlist to Spotify")
+        .required(true)
+        .index(1)
+}
+
+fn redirect_port_arg() -> Arg<'static, 'static> {
+    Arg::with_name("spotify-redirect-port")
+        .long("spotify-redirect-port")
+        .help("Port of the redirect URI for logging in to Spotify. http://127.0.0.1:<port> must be a redirect URI of the Spotify application")
+        .takes_value(true)
+        .default_value("8888")
+}
+
+fn quota_budget_arg() -> Arg<'static, 'static> {
+    Arg::with_name("quota-budget")
+        .long("quota-budget")
+        .help("Number of YouTube Data API quota units this tool may use per day. Conversions stop cleanly when it's used up")
+        .takes_value(true)
+        .default_value("10000")
+}
+
+/// Arguments of subcommands starting a new conversion
+fn new_job_args() -> Vec<Arg<'static, 'static>> {
+    vec![
+        Arg::with_name("target-playlist")
+            .long("target-playlist")
+            .help("ID or URL of an existing playlist in the target service to add the tracks to")
+            .takes_value(true),
+        Arg::with_name("dedup")
+            .long("dedup")
+            .help("Add videos several Spotify tracks resolve to only once ('within'), and also remove repeated videos already in the target playlist ('existing')")
+            .takes_value(true)
+            .possible_values(&["none", "within", "existing"])
+            .default_value("none"),
+        Arg::with_name("job")
+            .long("job")
+            .help("Path of the file progress is saved to. Defaults to a file per source playlist in the user's data directory")
+            .takes_value(true)
+    ]
+}
+
+/// Arguments of subcommands writing to the target service
+fn target_args() -> Vec<Arg<'static, 'static>> {
+    vec![
+        Arg::with_name("dry-run")
+            .long("dry-run")
+            .help("Match tracks and print the planned playlist, without changing anything in the target service. Converting from Spotify, this also skips logging in to Google"),
+        Arg::with_name("report")
+            .long("report")
+            .help("Write a report on every track to this file when done. The format follows the extension: .json, .csv or .html. Can be given multiple times")
+            .takes_value(true)
+            .multiple(true)
+            .number_of_values(1),
+        Arg::with_name("export-matches")
+            .long("export-matches")
+            .help("Write the items the tracks resolved to to this file when done: a .m3u8 of links other players can open, or a .json mapping. Combine with --dry-run to use the matches without a Google account. Can be given multiple times")
+            .takes_value(true)
+            .multiple(true)
+            .number_of_values(1),
+        Arg::with_name("privacy")
+            .long("privacy")
+            .help("Privacy status of the created playlist. Defaults to the target service's default")
+            .takes_value(true)
+            .possible_values(&["public", "unlisted", "private"]),
+        Arg::with_name("description-footer")
+            .long("description-footer")
+            .help("Text appended to the playlist description. '{url}' is replaced with the source playlist URL")
+            .takes_value(true)
+            .default_value("Converted from {url}"),
+        Arg::with_name("no-description-footer")
+            .long("no-description-footer")
+            .help("Don't append a footer to the playlist description"),
+        Arg::with_name("tags")
+            .long("tags")
+            .help("Comma separated tags for the created YouTube playlist")
+            .takes_value(true)
+            .use_delimiter(true),
+        Arg::with_name("default-language")
+            .long("default-language")
+            .help("Language of the playlist's title and description, e.g. 'en'")
+            .takes_value(true),
+        quota_budget_arg(),
+        Arg::with_name("sink")
+            .long("sink")
+            .help("How to add tracks to YouTube. 'innertube' uses YouTube Music's internal API, which has no quota but needs the cookies of a logged in browser session")
+            .takes_value(true)
+            .possible_values(&["data-api", "innertube"])
+            .default_value("data-api"),
+        Arg::with_name("ytm-cookie")
+            .long("ytm-cookie")
+            .help("The Cookie header of a request made by a logged in music.youtube.com tab, for the 'innertube' sink")
+            .takes_value(true),
+        Arg::with_name("ytm-cookie-file")
+            .long("ytm-cookie-file")
+            .help("Path of a file containing the YouTube Music Cookie header")
+            .takes_value(true)
+            .conflicts_with("ytm-cookie"),
+        Arg::with_name("batch-size")
+            .long("batch-size")
+            .help("Number of tracks to add per request with the 'innertube' sink")
+            .takes_value(true)
+            .default_value("50"),
+        redirect_port_arg()
+    ]
+}
+
+/// Arguments of subcommands matching tracks
+fn match_args() -> Vec<Arg<'static, 'static>> {
+    vec![
+        Arg::with_name("strategies")
+            .long("strategies")
+            .help("Search strategies to try, in order, until a result passes the match threshold")
+            .takes_value(true)
+            .use_delimiter(true)
+            .possible_values(&["full", "normalized", "album", "isrc"])
+            .default_value("full,normalized,album,isrc"),
+        Arg::with_name("threshold")
+            .long("threshold")
+            .help("Minimum score, between 0.0 and 1.0, a search result needs to be accepted as a match")
+            .takes_value(true)
+            .default_value("0.6"),
+        Arg::with_name("overrides")
+            .long("overrides")
+            .help("Path of a JSON file mapping Spotify track IDs or 'title – artist' to a YouTube video ID or 'skip'. Defaults to a file in the user's config directory")
+            .takes_value(true),
+        Arg::with_name("interactive")
+            .short("i")
+            .long("interactive")
+            .help("Ask which video to use for tracks without a confident match. Decisions are saved to the overrides file"),
+        Arg::with_name("review-candidates")
+            .long("review-candidates")
+            .help("Number of candidates to show when reviewing a track")
+            .takes_value(true)
+            .default_value("5")
+    ]
+}
+
+/// Arguments of subcommands using the match cache
+fn cache_args() -> Vec<Arg<'static, 'static>> {
+    let mut args = vec![
+        Arg::with_name("no-cache")
+            .long("no-cache")
+            .help("Don't read from or write to the match cache")
+            .conflicts_with("refresh-cache"),
+        Arg::with_name("refresh-cache")
+            .long("refresh-cache")
+            .help("Search every track again, replacing the cached matches")
+    ];
+    args.extend(cache_file_args());
+    args
+}
+
+/// Arguments locating the match cache
+fn cache_file_args() -> Vec<Arg<'static, 'static>> {
+    vec![
+        Arg::with_name("cache-ttl")
+            .long("cache-ttl")
+            .help("Number of days after which cached matches expire")
+            .takes_value(true)
+            .default_value("30"),
+        Arg::with_name("cache-file")
+            .long("cache-file")
+            .help("Path of the match cache. Defaults to a file in the user's cache directory")
+            .takes_value(true)
+    ]
+}
@@ -0,0 +1,25 @@
+use clap::ArgMatches;
+use log::info;
+use sp2ytm::accounts::Accounts;
+use crate::exit::{Context, Failure};
+
+/// Run a subcommand of the `accounts` subcommand
+pub fn run(matches: &ArgMatches) -> Result<(), Failure> {
+    let path = Accounts::default_path().context("Failed to determine accounts file location")?;
+    let mut accounts = Accounts::load(&path).context("Failed to load accounts")?;
+
+    match matches.subcommand() {
+        ("remove", Some(m)) => {
+            let name = m.value_of("name").expect("Missing required 'name'");
+            if !accounts.remove(name).context("Failed to remove account")? {
+                return Err(Failure::usage(format!("There is no account '{}'", name)));
+            }
+            info!("Removed account '{}'", name);
+        },
+        _ => for (name, account) in accounts.iter() {
+            println!("{}\t{} ({})", name, account.channel.title, account.channel.id);
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,21 @@
+use clap::ArgMatches;
+use sp2ytm::accounts::Accounts;
+use sp2ytm::env::Env;
+use crate::exit::{Context, Failure};
+
+/// Log in to the service given to the `auth` subcommand and print the access token, e.g. to check the credentials work.
+/// Logging in to Google with `--account` stores the account, replacing any account of that name
+pub fn run(matches: &ArgMatches, env: &Env) -> Result<(), Failure> {
+    let token = match (matches.value_of("service").expect("Missing required 'service'"), matches.value_of("account")) {
+        ("google", Some(name)) => {
+            let path = Accounts::default_path().context("Failed to determine accounts file location")?;
+            let mut accounts = Accounts::load(&path).context("Failed to load accounts")?;
+            super::add_google_account(env, &mut accounts, name)?
+        },
+        ("google", None) => super::google_login(env, None)?,
+        _ => super::spotify_login(matches, env)?
+    };
+
+    println!("{}", token);
+    Ok(())
+}
@@ -0,0 +1,31 @@
+use std::time::Duration;
+use clap::ArgMatches;
+use log::info;
+use sp2ytm::cache::MatchCache;
+use sp2ytm::env::Env;
+use crate::exit::{Context, Failure};
+
+/// Run a subcommand of the `cache` subcommand
+pub fn run(matches: &ArgMatches, env: &Env) -> Result<(), Failure> {
+    let path = super::cache_path(matches)?;
+    if matches.subcommand_name() == Some("path") {
+        println!("{}", path.display());
+        return Ok(());
+    }
+
+    // Opening the cache drops expired entries from the file
+    let ttl_days: u64 = super::setting(matches, "cache-ttl", env.cache_ttl)?;
+    let mut cache = MatchCache::open(&path, Duration::from_secs(ttl_days * 24 * 60 * 60), false).context("Failed to open match cache")?;
+
+    match matches.subcommand_name() {
+        Some("stats") => println!("{} cached matches in {}", cache.len(), path.display()),
+        Some("clear") => {
+            let count = cache.len();
+            cache.clear().context("Failed to clear match cache")?;
+            info!("Dropped {} cached matches", count);
+        },
+        _ => info!("{} cached matches left", cache.len())
+    }
+
+    Ok(())
+}
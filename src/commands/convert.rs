@@ -0,0 +1,222 @@
+use std::path::{Path, PathBuf};
+use clap::ArgMatches;
+use log::{debug, info, warn};
+use sp2ytm::{api, convert, job, matcher, playlists, service};
+use sp2ytm::env::Env;
+use crate::exit::{self, Context, Failure};
+
+/// Convert the playlist given to the `convert` or `sync` subcommand. Syncing adds to the playlist it was converted to before
+pub fn run(matches: &ArgMatches, env: &Env, sync: bool) -> Result<(), Failure> {
+    let dry_run = matches.is_present("dry-run");
+    let playlist_map_path = playlists::PlaylistMap::default_path().context("Failed to determine playlist map location")?;
+    let mut playlist_map = playlists::PlaylistMap::load(&playlist_map_path).context("Failed to load playlist map")?;
+
+    // Reading a YouTube playlist needs a Google login, which is then reused for anything else
+    let mut google_auth: Option<String> = None;
+
+    let playlist = matches.value_of("playlist-url").expect("Missing required 'playlist-url'");
+    let (direction, playlist_id, source) = {
+        let (direction, source): (_, Box<dyn service::PlaylistSource>) = if is_youtube_url(playlist) {
+            let auth = google_auth.insert(super::google_login(env, super::account(matches, env, None).as_deref())?);
+            (job::Direction::YouTubeToSpotify, Box::new(service::youtube::YouTubeSource { auth }))
+        } else if Path::new(playlist).is_file() {
+            (job::Direction::SpotifyToYouTube, Box::new(service::file::FileSource { path: PathBuf::from(playlist) }))
+        } else {
+            super::require_spotify(env)?;
+            (job::Direction::SpotifyToYouTube, Box::new(service::spotify::SpotifySource { env }))
+        };
+
+        let playlist_id = source.parse_playlist_id(playlist)
+            .ok_or_else(|| Failure::usage(format!("Invalid playlist url '{}'", playlist)))?;
+        debug!("Found playlist ID: {}", playlist_id);
+
+        let fetched = source.fetch_playlist(&playlist_id).context("Failed to fetch playlist")?;
+        debug!("Got {} tracks for '{}'", fetched.tracks.len(), fetched.name);
+        (direction, playlist_id, fetched)
+    };
+
+    let target = match matches.value_of("target-playlist") {
+        // Anything which isn't a playlist URL is taken to be a bare ID
+        Some(target) => Some(match direction {
+            job::Direction::SpotifyToYouTube => service::youtube::parse_playlist_id(target),
+            job::Direction::YouTubeToSpotify => service::spotify::parse_playlist_id(target)
+        }.unwrap_or(target).to_string()),
+        None if sync => {
+            let mapped = playlist_map.get(&playlist_id).map(str::to_string);
+            if mapped.is_none() {
+                warn!("This playlist has not been converted before, creating a new {} playlist", direction.target_name());
+            }
+
+            mapped
+        },
+        None => None
+    };
+
+    let job_path = match matches.value_of("job") {
+        Some(p) => PathBuf::from(p),
+        None => job::Job::default_path(&playlist_id).context("Failed to determine job file location")?
+    };
+
+    let existing = if job_path.exists() { job::Job::load(&job_path).ok() } else { None };
+
+    // Converting again would lose the playlist and matches of an interrupted conversion, and create another playlist.
+    // A job which failed before doing anything, e.g. because logging in failed, has nothing to lose
+    let started = |j: &job::Job| j.target_playlist_id.is_some() || j.tracks.iter().any(|t| t.status != job::Status::Pending);
+    if let Some(unfinished) = existing.as_ref().filter(|j| !sync && !dry_run && !j.is_complete() && started(j)) {
+        return Err(Failure::usage(format!(
+            "An unfinished conversion of '{}' is saved in {}. Resume it with: {} resume {}, or pass --job to start over in another file",
+            unfinished.source.name, job_path.display(), env!("CARGO_PKG_NAME"), job_path.display()
+        )));
+    }
+
+    // Syncing keeps using the account the playlist was created with, unless another one is given
+    let recorded = existing.filter(|_| sync).and_then(|j| j.account);
+
+    let dedup = super::setting(matches, "dedup", env.dedup)?;
+    let mut job = job::Job::new(&job_path, direction, &playlist_id, source, target, matches.is_present("remove-missing"), dedup);
+    job.account = super::account(matches, env, recorded);
+    if !dry_run {
+        job.save().context("Failed to save job")?;
+        info!("Saving progress to {}", job_path.display());
+    }
+
+    execute(matches, env, job, &mut playlist_map, google_auth)
+}
+
+/// Resume the conversion saved in the job file given to the `resume` subcommand
+pub fn resume(matches: &ArgMatches, env: &Env) -> Result<(), Failure> {
+    let playlist_map_path = playlists::PlaylistMap::default_path().context("Failed to determine playlist map location")?;
+    let mut playlist_map = playlists::PlaylistMap::load(&playlist_map_path).context("Failed to load playlist map")?;
+
+    let path = matches.value_of("job").expect("Missing required 'job'");
+    let mut job = job::Job::load(Path::new(path)).context("Failed to load job")?;
+    job.account = super::account(matches, env, job.account.take());
+    info!("Resuming conversion of '{}'", job.source.name);
+
+    execute(matches, env, job, &mut playlist_map, None)
+}
+
+/// Resolve and add the tracks of `job`, or only print the plan with `--dry-run`
+fn execute(matches: &ArgMatches, env: &Env, mut job: job::Job, playlist_map: &mut playlists::PlaylistMap, mut google_auth: Option<String>) -> Result<(), Failure> {
+    let playlist_options = convert::PlaylistOptions {
+        privacy: match matches.value_of("privacy") {
+            Some(p) => Some(p.parse().context("Invalid value for --privacy")?),
+            None => env.privacy
+        },
+        footer: if matches.is_present("no-description-footer") {
+            None
+        } else {
+            Some(super::setting(matches, "description-footer", env.description_footer.clone())?)
+        },
+        tags: match matches.values_of("tags") {
+            Some(tags) => tags.map(str::to_string).collect(),
+            None => env.tags.clone().unwrap_or_default()
+        },
+        default_language: matches.value_of("default-language")
+            .map(str::to_string)
+            .or_else(|| env.default_language.clone())
+    };
+
+    let mut resolver = super::build_resolver(matches, env, job.direction)?;
+
+    if matches.is_present("dry-run") {
+        // Never write the job file, so a dry run doesn't clobber the progress of a real conversion of the same playlist
+        job.detach();
+
+        // Searching Spotify needs a token, but an application token suffices, so no user login is needed
+        let catalog: Box<dyn matcher::Catalog> = match job.direction {
+            job::Direction::SpotifyToYouTube => Box::new(service::youtube::YouTubeMusic),
+            job::Direction::YouTubeToSpotify => {
+                super::require_spotify(env)?;
+                Box::new(service::spotify::SpotifyCatalog {
+                    auth: api::spotify::get_login_token(env).context("Failed to log in to Spotify")?
+                })
+            }
+        };
+
+        let result = convert::dry_run(&mut job, &mut resolver, &playlist_options, catalog.as_ref());
+        super::write_reports(matches, &job);
+        print_plan(&job, &result.context("Failed to plan playlist")?);
+        return Ok(());
+    }
+
+    let sink: String = super::setting(matches, "sink", env.sink.clone())?;
+    let sink: Box<dyn service::PlaylistSink> = match (job.direction, sink.as_str()) {
+        (job::Direction::YouTubeToSpotify, _) => Box::new(service::spotify::SpotifySink::new(super::spotify_login(matches, env)?)),
+        (_, "innertube") => {
+            // The session is that of the browser the cookie was taken from, whichever Google account that is
+            if matches.is_present("account") {
+                return Err(Failure::usage("--account can't be used with the 'innertube' sink, which adds to the channel of the browser session the cookie is from"));
+            }
+            if let Some(account) = &job.account {
+                warn!("Ignoring Google account '{}', the 'innertube' sink adds to the channel of the browser session the cookie is from", account);
+            }
+
+            let cookie_file = matches.value_of("ytm-cookie-file").map(PathBuf::from).or_else(|| env.ytm_cookie_file.clone());
+            let cookie = match (matches.value_of("ytm-cookie"), cookie_file) {
+                (Some(cookie), _) => cookie.to_string(),
+                (None, Some(path)) => std::fs::read_to_string(path).context("Failed to read YouTube Music cookie file")?,
+                (None, None) => return Err(Failure::usage("--ytm-cookie or --ytm-cookie-file is required for the 'innertube' sink"))
+            };
+
+            Box::new(service::youtube::InnerTubeSink {
+                session: api::ytmusic::Session::from_cookie(&cookie).context("Invalid YouTube Music cookie")?,
+                batch_size: super::parse(matches, "batch-size")?
+            })
+        },
+        (_, "data-api") => {
+            let auth = match google_auth.take() {
+                Some(auth) => auth,
+                None => super::google_login(env, job.account.as_deref())?
+            };
+
+            let channel = api::youtube::get_my_channel(&auth).context("Failed to get the YouTube channel")?;
+            info!("Adding to YouTube channel '{}' ({})", channel.title, channel.id);
+            Box::new(service::youtube::DataApiSink { auth })
+        },
+        (_, other) => return Err(Failure::usage(format!("Unknown sink '{}'", other)))
+    };
+
+    let result = convert::run(&mut job, &mut resolver, playlist_map, &playlist_options, sink.as_ref());
+    super::write_reports(matches, &job);
+    match result.context("Failed to convert playlist")? {
+        true => Ok(()),
+        false => Err(Failure::new(exit::INCOMPLETE, "Some tracks could not be converted"))
+    }
+}
+
+/// Print the playlist a dry run of `job` would produce
+fn print_plan(job: &job::Job, plan: &convert::Plan) {
+    let service = job.direction.target_name();
+    match &plan.target_playlist_id {
+        Some(id) => println!("Would update {} playlist {} to:", service, id),
+        None => println!("Would create {} playlist '{}' with:", service, plan.title)
+    }
+
+    for planned in &plan.tracks {
+        let t = &job.tracks[planned.index];
+        let source = format!("{} - {}", t.track.name, t.track.artist());
+        match (planned.position, &t.found, t.status) {
+            (Some(position), Some(m), _) => {
+                let item = if m.candidate.title.is_empty() {
+                    m.candidate.id.clone()
+                } else {
+                    format!("{} - {} ({})", m.candidate.title, m.candidate.artists.join(", "), m.candidate.id)
+                };
+                println!("{:>4}. {} -> {} [score {:.2}, {}]", position, source, item, m.score, m.strategy);
+            },
+            (_, _, job::Status::Duplicate) => println!("   -  {} -> duplicate of track {}", source, t.duplicate_of.map(|o| o + 1).unwrap_or_default()),
+            (_, _, job::Status::Skipped) => println!("   -  {} -> skipped", source),
+            (_, _, job::Status::Failed) => println!("   -  {} -> failed: {}", source, t.error.as_deref().unwrap_or_default()),
+            _ => println!("   -  {} -> not found", source)
+        }
+    }
+
+    let added = plan.tracks.iter().filter(|t| t.position.is_some()).count();
+    info!("Dry run: {} of {} tracks would be in the playlist. Nothing was changed on {}", added, job.tracks.len(), service);
+}
+
+/// Whether `url` links to YouTube or YouTube Music rather than Spotify
+fn is_youtube_url(url: &str) -> bool {
+    url.contains("youtube.com/") || url.contains("youtu.be/")
+}
@@ -0,0 +1,23 @@
+use std::path::Path;
+use clap::ArgMatches;
+use log::info;
+use sp2ytm::env::Env;
+use sp2ytm::playlist_file;
+use sp2ytm::service::{spotify::SpotifySource, PlaylistSource};
+use crate::exit::{Context, Failure};
+
+/// Write the Spotify playlist given to the `export` subcommand to a file
+pub fn run(matches: &ArgMatches, env: &Env) -> Result<(), Failure> {
+    super::require_spotify(env)?;
+    let url = matches.value_of("playlist-url").expect("Missing required 'playlist-url'");
+    let output = matches.value_of("output").expect("Missing required 'output'");
+
+    let source = SpotifySource { env };
+    let playlist_id = source.parse_playlist_id(url)
+        .ok_or_else(|| Failure::usage(format!("Invalid playlist url '{}'", url)))?;
+
+    let playlist = source.fetch_playlist(&playlist_id).context("Failed to fetch playlist")?;
+    playlist_file::write(&playlist, Path::new(output)).context("Failed to write playlist file")?;
+    info!("Exported {} tracks of '{}' to {}", playlist.tracks.len(), playlist.name, output);
+    Ok(())
+}
@@ -0,0 +1,228 @@
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use clap::ArgMatches;
+use log::{debug, error, info};
+use sp2ytm::cache::MatchCache;
+use sp2ytm::accounts::{Account, Accounts};
+use sp2ytm::{api, env, job, mapping, matcher, oauth2, overrides, resolver};
+use crate::exit::{self, Context, Failure};
+
+pub mod accounts;
+pub mod auth;
+pub mod cache;
+pub mod convert;
+pub mod export;
+pub mod report;
+pub mod resolve;
+
+/// Run the subcommand `name` with the profile of the config file selected by `--config` and `--profile`
+pub fn run(name: &str, matches: &ArgMatches) -> Result<(), Failure> {
+    let env = env(matches)?;
+    sp2ytm::api::set_rate_limits(&env.rate_limits);
+    if matches!(name, "convert" | "sync" | "resume" | "auth") {
+        init_quota(matches, &env)?;
+    }
+
+    match name {
+        "convert" => convert::run(matches, &env, false),
+        "sync" => convert::run(matches, &env, true),
+        "resume" => convert::resume(matches, &env),
+        "export" => export::run(matches, &env),
+        "resolve" => resolve::run(matches, &env),
+        "auth" => auth::run(matches, &env),
+        "accounts" => accounts::run(matches),
+        "cache" => cache::run(matches, &env),
+        "report" => report::run(matches),
+        _ => unreachable!("Unknown subcommand '{}'", name)
+    }
+}
+
+/// Load the config profile, with the credentials given on the command line taking precedence.
+/// Missing credentials are only reported once they are needed
+fn env(matches: &ArgMatches) -> Result<env::Env, Failure> {
+    let mut env = match matches.value_of("config") {
+        Some(path) if !Path::new(path).exists() => return Err(Failure::usage(format!("Config file {} does not exist", path))),
+        Some(path) => env::Env::load(Path::new(path), matches.value_of("profile")),
+        None => env::Env::default_path().and_then(|path| env::Env::load(&path, matches.value_of("profile")))
+    }.context("Failed to load config")?;
+
+    for (name, value) in [
+        ("google-client-id", &mut env.google_client_id),
+        ("google-client-secret", &mut env.google_client_secret),
+        ("spotify-client-id", &mut env.spotify_client_id),
+        ("spotify-client-secret", &mut env.spotify_client_secret)
+    ] {
+        if let Some(v) = matches.value_of(name) {
+            *value = v.to_string();
+        }
+    }
+
+    Ok(env)
+}
+
+/// Start tracking the YouTube Data API quota, before the first request is made
+fn init_quota(matches: &ArgMatches, env: &env::Env) -> Result<(), Failure> {
+    let path = api::youtube::quota::default_path().context("Failed to determine quota usage file location")?;
+    api::youtube::quota::init(&path, setting(matches, "quota-budget", env.quota_budget)?).context("Failed to load quota usage")
+}
+
+/// Parse the value of an argument with a default value
+pub fn parse<T: FromStr>(matches: &ArgMatches, name: &str) -> Result<T, Failure> {
+    matches.value_of(name)
+        .unwrap_or_else(|| panic!("Missing default '{}'", name))
+        .parse()
+        .map_err(|_| Failure::usage(format!("Invalid value for --{}", name)))
+}
+
+/// The value of the argument `name` if given on the command line, otherwise the `configured` value, otherwise the argument's default
+pub fn setting<T: FromStr>(matches: &ArgMatches, name: &str, configured: Option<T>) -> Result<T, Failure> {
+    match configured {
+        Some(value) if matches.occurrences_of(name) == 0 => Ok(value),
+        _ => parse(matches, name)
+    }
+}
+
+/// Fail if the credential `value` is neither given on the command line as `--<name>` nor in the config file
+fn require(value: &str, name: &str) -> Result<(), Failure> {
+    if value.is_empty() {
+        return Err(Failure::usage(format!("--{} or {} in the config file is required", name, name.replace('-', "_"))));
+    }
+
+    Ok(())
+}
+
+/// Fail if the Spotify application credentials were not given
+pub fn require_spotify(env: &env::Env) -> Result<(), Failure> {
+    require(&env.spotify_client_id, "spotify-client-id")?;
+    require(&env.spotify_client_secret, "spotify-client-secret")
+}
+
+/// The name of the Google account given with `--account`, otherwise the `recorded` one, otherwise the configured one
+pub fn account(matches: &ArgMatches, env: &env::Env, recorded: Option<String>) -> Option<String> {
+    matches.value_of("account")
+        .map(str::to_string)
+        .or(recorded)
+        .or_else(|| env.account.clone())
+}
+
+/// Log in to Google, failing if the credentials needed for it were not given.
+/// A stored `account` is logged in to without the browser, an unknown one is logged in to and stored
+pub fn google_login(env: &env::Env, account: Option<&str>) -> Result<String, Failure> {
+    require(&env.google_client_id, "google-client-id")?;
+    require(&env.google_client_secret, "google-client-secret")?;
+
+    let name = match account {
+        Some(name) => name,
+        None => {
+            debug!("Performing authentication with Google");
+            return Ok(oauth2::do_oauth(&oauth2::Provider::google(env)).context("Unable to log in to Google")?.access_token);
+        }
+    };
+
+    let path = Accounts::default_path().context("Failed to determine accounts file location")?;
+    let mut accounts = Accounts::load(&path).context("Failed to load accounts")?;
+    match accounts.get(name) {
+        Some(stored) => {
+            debug!("Refreshing the access token of Google account '{}'", name);
+            oauth2::refresh(&oauth2::Provider::google(env), &stored.refresh_token)
+                .context(&format!("Unable to log in to Google account '{}'. Log in again with '{} auth google --account {}'", name, env!("CARGO_PKG_NAME"), name))
+        },
+        None => {
+            info!("Google account '{}' is not logged in yet", name);
+            add_google_account(env, &mut accounts, name)
+        }
+    }
+}
+
+/// Log in to Google in the browser and store the account as `name`, replacing any account of that name
+pub fn add_google_account(env: &env::Env, accounts: &mut Accounts, name: &str) -> Result<String, Failure> {
+    require(&env.google_client_id, "google-client-id")?;
+    require(&env.google_client_secret, "google-client-secret")?;
+
+    debug!("Performing authentication with Google");
+    let tokens = oauth2::do_oauth(&oauth2::Provider::google(env)).context("Unable to log in to Google")?;
+    let refresh_token = tokens.refresh_token.ok_or_else(|| Failure::new(exit::AUTH, "Google granted no refresh token to store the account with"))?;
+    let channel = api::youtube::get_my_channel(&tokens.access_token).context("Failed to get the account's YouTube channel")?;
+
+    info!("Logged in to YouTube channel '{}' ({}), stored as account '{}'", channel.title, channel.id, name);
+    accounts.set(name, Account { refresh_token, channel }).context("Failed to store account")?;
+    Ok(tokens.access_token)
+}
+
+/// Log in to Spotify as a user, failing if the client ID was not given
+pub fn spotify_login(matches: &ArgMatches, env: &env::Env) -> Result<String, Failure> {
+    require(&env.spotify_client_id, "spotify-client-id")?;
+    let port = setting(matches, "spotify-redirect-port", env.spotify_redirect_port)?;
+
+    debug!("Performing authentication with Spotify");
+    Ok(oauth2::do_oauth(&oauth2::Provider::spotify(env, port)).context("Unable to log in to Spotify")?.access_token)
+}
+
+/// Open the match cache, or `None` if disabled with `--no-cache`
+pub fn open_cache(matches: &ArgMatches, env: &env::Env) -> Result<Option<MatchCache>, Failure> {
+    if matches.is_present("no-cache") {
+        return Ok(None);
+    }
+
+    let path = cache_path(matches)?;
+    let ttl_days: u64 = setting(matches, "cache-ttl", env.cache_ttl)?;
+    let cache = MatchCache::open(&path, std::time::Duration::from_secs(ttl_days * 24 * 60 * 60), matches.is_present("refresh-cache"))
+        .context("Failed to open match cache")?;
+    Ok(Some(cache))
+}
+
+/// The location of the match cache
+pub fn cache_path(matches: &ArgMatches) -> Result<PathBuf, Failure> {
+    match matches.value_of("cache-file") {
+        Some(p) => Ok(PathBuf::from(p)),
+        None => MatchCache::default_path().context("Failed to determine match cache location")
+    }
+}
+
+/// Build the resolver for a conversion in `direction`
+pub fn build_resolver(matches: &ArgMatches, env: &env::Env, direction: job::Direction) -> Result<resolver::Resolver, Failure> {
+    let options = matcher::MatchOptions {
+        strategies: match &env.strategies {
+            Some(strategies) if matches.occurrences_of("strategies") == 0 => strategies.clone(),
+            _ => matches.values_of("strategies")
+                .expect("Missing default 'strategies'")
+                .map(|s| s.parse().map_err(|_| Failure::usage("Invalid value for --strategies")))
+                .collect::<Result<_, _>>()?
+        },
+        threshold: setting(matches, "threshold", env.threshold)?
+    };
+
+    let overrides_path = match matches.value_of("overrides") {
+        Some(p) => PathBuf::from(p),
+        None => overrides::Overrides::default_path(direction).context("Failed to determine overrides file location")?
+    };
+    let overrides = overrides::Overrides::load(&overrides_path).context("Failed to load overrides")?;
+
+    Ok(resolver::Resolver {
+        options,
+        overrides,
+        cache: open_cache(matches, env)?,
+        review: if matches.is_present("interactive") {
+            Some(parse(matches, "review-candidates")?)
+        } else {
+            None
+        }
+    })
+}
+
+/// Write a report on `job` to every file passed with `--report`, and its matches to every file passed with `--export-matches`
+pub fn write_reports(matches: &ArgMatches, job: &job::Job) {
+    for path in matches.values_of("report").into_iter().flatten() {
+        match sp2ytm::report::write(job, Path::new(path)) {
+            Ok(_) => info!("Wrote report to {}", path),
+            Err(e) => error!("Failed to write report to {}: {}", path, e)
+        }
+    }
+
+    for path in matches.values_of("export-matches").into_iter().flatten() {
+        match mapping::write(job, Path::new(path)) {
+            Ok(_) => info!("Wrote matches to {}", path),
+            Err(e) => error!("Failed to write matches to {}: {}", path, e)
+        }
+    }
+}
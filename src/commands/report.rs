@@ -0,0 +1,16 @@
+use std::path::Path;
+use clap::ArgMatches;
+use log::info;
+use sp2ytm::job::Job;
+use crate::exit::{Context, Failure};
+
+/// Write reports on the job file given to the `report` subcommand
+pub fn run(matches: &ArgMatches) -> Result<(), Failure> {
+    let job = Job::load(Path::new(matches.value_of("job").expect("Missing required 'job'"))).context("Failed to load job")?;
+    for path in matches.values_of("output").expect("Missing required 'output'") {
+        sp2ytm::report::write(&job, Path::new(path)).context(&format!("Failed to write report to {}", path))?;
+        info!("Wrote report to {}", path);
+    }
+
+    Ok(())
+}
@@ -0,0 +1,83 @@
+use std::io::{BufRead, Write};
+use clap::ArgMatches;
+use log::{error, warn};
+use sp2ytm::{api, job, resolver, service, Error};
+use sp2ytm::env::Env;
+use crate::exit::{self, Context, Failure};
+
+/// Print the YouTube Music link of every Spotify track given to the `resolve` subcommand, or read from stdin if none were given.
+/// Prints one line per track, which is empty if the track was not found, so the output lines up with the input.
+/// Fails with the class of the last error if any track failed to resolve, or as incomplete if any wasn't found
+pub fn run(matches: &ArgMatches, env: &Env) -> Result<(), Failure> {
+    super::require_spotify(env)?;
+    let mut resolver = super::build_resolver(matches, env, job::Direction::SpotifyToYouTube)?;
+
+    let inputs: Vec<String> = match matches.values_of("tracks") {
+        Some(tracks) => tracks.map(str::to_string).collect(),
+        None => std::io::stdin().lock()
+            .lines()
+            .collect::<Result<Vec<_>, _>>()
+            .context("Failed to read stdin")?
+            .into_iter()
+            .filter(|l| !l.trim().is_empty())
+            .collect()
+    };
+
+    let ids: Vec<String> = inputs.iter()
+        .filter_map(|input| service::spotify::parse_track_id(input))
+        .map(str::to_string)
+        .collect();
+
+    let auth = api::spotify::get_login_token(env).context("Failed to log in to Spotify")?;
+    let mut tracks = api::spotify::get_tracks(&ids, &auth).context("Failed to look up tracks")?.into_iter();
+    let catalog = service::youtube::YouTubeMusic;
+    let mut stdout = std::io::stdout().lock();
+    let mut not_found = 0;
+    let mut failed = 0;
+    let mut last_error: Option<Error> = None;
+
+    for input in &inputs {
+        let track = match service::spotify::parse_track_id(input) {
+            Some(id) => match tracks.next().flatten() {
+                Some(t) => t,
+                None => {
+                    warn!("Spotify has no track {}", id);
+                    not_found += 1;
+                    writeln!(stdout).context("Failed to write to stdout")?;
+                    continue;
+                }
+            },
+            None => {
+                warn!("'{}' is not a Spotify track", input.trim());
+                not_found += 1;
+                writeln!(stdout).context("Failed to write to stdout")?;
+                continue;
+            }
+        };
+
+        let url = match resolver.resolve(&track, &catalog) {
+            Ok(resolver::Resolution::Matched(m)) => job::Direction::SpotifyToYouTube.target_item_url(&m.candidate.id),
+            Ok(resolver::Resolution::Skipped) => String::new(),
+            Ok(resolver::Resolution::NotFound) => {
+                warn!("Unable to find a match for '{} - {}'", track.name, track.artist());
+                not_found += 1;
+                String::new()
+            },
+            Err(e) => {
+                error!("Failed to resolve '{} - {}': {}", track.name, track.artist(), e);
+                failed += 1;
+                last_error = Some(e);
+                String::new()
+            }
+        };
+
+        writeln!(stdout, "{}", url).context("Failed to write to stdout")?;
+        stdout.flush().context("Failed to write to stdout")?;
+    }
+
+    match last_error {
+        Some(e) => Err(e).context(&format!("Failed to resolve {} of {} tracks, the last error was", failed, inputs.len())),
+        None if not_found > 0 => Err(Failure::new(exit::INCOMPLETE, format!("{} of {} tracks were not found", not_found, inputs.len()))),
+        None => Ok(())
+    }
+}
@@ -0,0 +1,179 @@
+use crate::Result;
+use log::{debug, info, warn};
+use crate::job::{Job, Status};
+use crate::matcher::Catalog;
+use crate::playlists::PlaylistMap;
+use crate::resolver::{Resolution, Resolver};
+use crate::service::{PlaylistDetails, PlaylistSink, Privacy};
+use crate::dedup::{self, Dedup};
+
+/// Metadata to give playlists created for a conversion
+pub struct PlaylistOptions {
+    /// `None` leaves the privacy status at the target service's default
+    pub privacy: Option<Privacy>,
+    /// Text appended to the description. `{url}` is replaced with the source playlist URL. Left out for sources without a URL
+    pub footer: Option<String>,
+    pub tags: Vec<String>,
+    pub default_language: Option<String>
+}
+
+/// Run `job` until every track is inserted or has failed, saving progress after every step.
+/// Tracks which failed in an earlier run are retried. Returns whether every track and playlist change succeeded
+///
+/// ## Errors
+/// - When the target playlist can't be created or listed
+/// - When the job can't be saved
+pub fn run(job: &mut Job, resolver: &mut Resolver, playlist_map: &mut PlaylistMap, options: &PlaylistOptions, sink: &dyn PlaylistSink) -> Result<bool> {
+    for t in job.tracks.iter_mut().filter(|t| t.status == Status::Failed) {
+        t.status = if t.found.is_some() { Status::Matched } else { Status::Pending };
+        t.error = None;
+    }
+
+    sink.prepare(job);
+    resolve(job, resolver, sink.catalog())?;
+    if job.dedup != Dedup::None {
+        dedup::mark_duplicates(job);
+        job.save()?;
+    }
+
+    let (playlist_id, created) = match job.target_playlist_id.clone() {
+        Some(id) => (id, false),
+        None => {
+            debug!("Creating {} playlist", sink.name());
+            let id = sink.create_playlist(&playlist_details(job, options))?;
+            job.target_playlist_id = Some(id.clone());
+            job.save()?;
+            (id, true)
+        }
+    };
+
+    playlist_map.set(&job.source.playlist_id, &playlist_id)?;
+    let failures = sink.add_items(job, &playlist_id, created)?;
+
+    let failed: Vec<_> = job.tracks.iter()
+        .filter(|t| t.status == Status::Failed)
+        .collect();
+
+    let complete = failed.is_empty() && failures == 0;
+    if complete {
+        info!("Done");
+    } else {
+        match job.path() {
+            Some(path) => warn!("Done, but {} tracks failed. Resume with: {} resume {}", failed.len(), env!("CARGO_PKG_NAME"), path.display()),
+            None => warn!("Done, but {} tracks failed", failed.len())
+        }
+        for t in failed {
+            warn!("    {} - {}: {}", t.track.name, t.track.artist(), t.error.as_deref().unwrap_or_default());
+        }
+    }
+
+    Ok(complete)
+}
+
+/// The playlist a dry run would produce
+pub struct Plan {
+    /// The playlist which would be updated. `None` if one would be created
+    pub target_playlist_id: Option<String>,
+    /// Title of the playlist which would be created
+    pub title: String,
+    /// Every track of the job, in order
+    pub tracks: Vec<PlannedTrack>
+}
+
+/// What would happen to a track of the job
+pub struct PlannedTrack {
+    /// Index of the track in the job's tracks
+    pub index: usize,
+    /// One-based position of the track in the playlist. `None` if it would be left out, the track's status says why
+    pub position: Option<usize>
+}
+
+/// Resolve every pending track of `job` like [run] does, but instead of touching the target playlist, return the playlist it would produce.
+/// Listing the target playlist's current items would need a login, so the plan only describes the desired end state
+///
+/// ## Errors
+/// - When the job can't be saved
+pub fn dry_run(job: &mut Job, resolver: &mut Resolver, options: &PlaylistOptions, catalog: &dyn Catalog) -> Result<Plan> {
+    resolve(job, resolver, catalog)?;
+    if job.dedup != Dedup::None {
+        dedup::mark_duplicates(job);
+    }
+
+    let mut position = 0;
+    let tracks = job.tracks.iter()
+        .enumerate()
+        .map(|(index, t)| PlannedTrack {
+            index,
+            position: match (t.status, &t.found) {
+                (Status::Matched | Status::Inserted, Some(_)) => {
+                    position += 1;
+                    Some(position)
+                },
+                _ => None
+            }
+        })
+        .collect();
+
+    Ok(Plan {
+        target_playlist_id: job.target_playlist_id.clone(),
+        title: playlist_details(job, options).title,
+        tracks
+    })
+}
+
+/// Resolve every pending track against `catalog`
+fn resolve(job: &mut Job, resolver: &mut Resolver, catalog: &dyn Catalog) -> Result<()> {
+    debug!("Resolving all tracks to {} IDs", job.direction.target_name());
+    for i in 0..job.tracks.len() {
+        if job.tracks[i].status != Status::Pending {
+            continue;
+        }
+
+        let t = &mut job.tracks[i];
+        match resolver.resolve(&t.track, catalog) {
+            Ok(Resolution::Matched(m)) => {
+                debug!("Matched '{} - {}' ({}) to {} using strategy '{}' (score {:.2})", t.track.name, t.track.artist(), t.track.id.as_deref().unwrap_or("local file"), m.candidate.id, m.strategy, m.score);
+                t.status = Status::Matched;
+                t.found = Some(m);
+            },
+            Ok(Resolution::Skipped) => {
+                info!("Skipping '{} - {}'", t.track.name, t.track.artist());
+                t.status = Status::Skipped;
+            },
+            Ok(Resolution::NotFound) => {
+                warn!("Unable to find a match for '{} - {}'", t.track.name, t.track.artist());
+                t.status = Status::NotFound;
+            },
+            Err(e) => {
+                warn!("Failed to resolve '{} - {}': {}", t.track.name, t.track.artist(), e);
+                t.status = Status::Failed;
+                t.error = Some(e.to_string());
+            }
+        }
+
+        job.save()?;
+    }
+
+    Ok(())
+}
+
+/// Build the metadata of the playlist to create for `job`
+fn playlist_details(job: &Job, options: &PlaylistOptions) -> PlaylistDetails {
+    // Without a link, e.g. for playlist files, the footer would have nothing to point to
+    let footer = options.footer.as_ref()
+        .filter(|_| !job.source.url.is_empty())
+        .map(|f| f.replace("{url}", &job.source.url));
+    let description = job.source.description.iter()
+        .chain(footer.iter())
+        .map(String::as_str)
+        .collect::<Vec<_>>()
+        .join("\n\n");
+
+    PlaylistDetails {
+        title: job.source.name.clone(),
+        description,
+        privacy: options.privacy,
+        tags: options.tags.clone(),
+        default_language: options.default_language.clone()
+    }
+}
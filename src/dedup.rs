@@ -0,0 +1,159 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::str::FromStr;
+use crate::Error;
+use log::info;
+use serde::{Serialize, Deserialize};
+use crate::job::{Job, Status};
+
+/// Which duplicate videos to leave out of the YouTube playlist
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Dedup {
+    /// Keep duplicates
+    #[default]
+    None,
+    /// Add every video only once, even if several Spotify tracks resolve to it
+    Within,
+    /// Like `Within`, and also remove videos which occur more than once in the target playlist
+    Existing
+}
+
+impl fmt::Display for Dedup {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::None => "none",
+            Self::Within => "within",
+            Self::Existing => "existing"
+        })
+    }
+}
+
+impl FromStr for Dedup {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "none" => Ok(Self::None),
+            "within" => Ok(Self::Within),
+            "existing" => Ok(Self::Existing),
+            _ => Err(Error::invalid_value("dedup mode", s))
+        }
+    }
+}
+
+/// Mark every track resolving to a video an earlier track already resolved to as a duplicate of that track,
+/// and report which tracks collapsed together
+pub fn mark_duplicates(job: &mut Job) {
+    let mut first: HashMap<String, usize> = HashMap::new();
+    let mut collapsed: Vec<(usize, usize)> = Vec::new();
+
+    for (i, t) in job.tracks.iter_mut().enumerate() {
+        if !matches!(t.status, Status::Matched | Status::Inserted) {
+            continue;
+        }
+
+        let video_id = match &t.found {
+            Some(m) => m.candidate.id.clone(),
+            None => continue
+        };
+
+        match first.get(&video_id) {
+            Some(&original) => {
+                t.status = Status::Duplicate;
+                t.duplicate_of = Some(original);
+                collapsed.push((original, i));
+            },
+            None => {
+                first.insert(video_id, i);
+            }
+        }
+    }
+
+    for (original, duplicate) in collapsed {
+        let (a, b) = (&job.tracks[original].track, &job.tracks[duplicate].track);
+        info!("'{} - {}' resolves to the same {} track as '{} - {}', adding it only once", b.name, b.artist(), job.direction.target_name(), a.name, a.artist());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+    use super::*;
+    use crate::job::Direction;
+    use crate::matcher::Match;
+    use crate::service::{Playlist, PlaylistTrack};
+
+    /// A job whose tracks have the given status and, if any, match
+    fn job(tracks: &[(Status, Option<&str>)]) -> Job {
+        let playlist = Playlist {
+            tracks: (0..tracks.len())
+                .map(|i| PlaylistTrack {
+                    id: None,
+                    name: format!("Track {}", i),
+                    artists: Vec::new(),
+                    album: None,
+                    isrc: None,
+                    duration_ms: None
+                })
+                .collect(),
+            name: "Mix".to_string(),
+            description: None,
+            url: String::new()
+        };
+
+        let mut job = Job::new(Path::new("job.json"), Direction::SpotifyToYouTube, "mix", playlist, None, false, Dedup::Within);
+        job.detach();
+        for (t, (status, video_id)) in job.tracks.iter_mut().zip(tracks) {
+            t.status = *status;
+            t.found = video_id.map(Match::manual);
+        }
+
+        job
+    }
+
+    #[test]
+    fn marks_later_occurrences() {
+        let mut job = job(&[
+            (Status::Inserted, Some("a")),
+            (Status::Matched, Some("b")),
+            (Status::Matched, Some("a")),
+            (Status::Matched, Some("b")),
+            (Status::Inserted, Some("a"))
+        ]);
+        mark_duplicates(&mut job);
+
+        let result: Vec<_> = job.tracks.iter().map(|t| (t.status, t.duplicate_of)).collect();
+        assert_eq!(result, vec![
+            (Status::Inserted, None),
+            (Status::Matched, None),
+            (Status::Duplicate, Some(0)),
+            (Status::Duplicate, Some(1)),
+            (Status::Duplicate, Some(0))
+        ]);
+    }
+
+    #[test]
+    fn ignores_unmatched_tracks() {
+        // Only matched and inserted tracks count, so the first one of those is kept even if an earlier track had the same video
+        let mut job = job(&[
+            (Status::Failed, Some("a")),
+            (Status::Skipped, Some("a")),
+            (Status::NotFound, None),
+            (Status::Matched, Some("a")),
+            (Status::Pending, None),
+            (Status::Matched, Some("a"))
+        ]);
+        mark_duplicates(&mut job);
+
+        let result: Vec<_> = job.tracks.iter().map(|t| (t.status, t.duplicate_of)).collect();
+        assert_eq!(result, vec![
+            (Status::Failed, None),
+            (Status::Skipped, None),
+            (Status::NotFound, None),
+            (Status::Matched, None),
+            (Status::Pending, None),
+            (Status::Duplicate, Some(3))
+        ]);
+    }
+}
@@ -1,7 +1,201 @@
-#[derive(Clone)]
+use std::fs;
+use std::num::NonZeroU32;
+use std::path::{Path, PathBuf};
+use serde::Deserialize;
+use crate::{Error, Result};
+use crate::service::Privacy;
+use crate::dedup::Dedup;
+use crate::matcher::Strategy;
+
+/// Credentials of the applications registered with Google and Spotify, and the defaults of options not given on the command line.
+///
+/// Loaded from a TOML config file. Settings at the top level apply to every profile, and the tables under `profiles`
+/// hold named profiles overriding them, e.g. for different Google accounts or API projects:
+///
+/// ```toml
+/// default_profile = "work"
+/// spotify_client_id = "..."
+/// spotify_client_secret = "..."
+/// threshold = 0.7
+///
+/// [profiles.work]
+/// account = "label"
+/// google_client_id = "..."
+/// google_client_secret = "..."
+/// privacy = "unlisted"
+///
+/// [profiles.work.rate_limits]
+/// youtube = 5
+/// ```
+#[derive(Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
 pub struct Env {
     pub google_client_id:       String,
     pub google_client_secret:   String,
     pub spotify_client_id:      String,
-    pub spotify_client_secret:  String
-}
\ No newline at end of file
+    pub spotify_client_secret:  String,
+
+    /// Name of the stored Google account to log in with
+    pub account:                Option<String>,
+    /// Privacy status of created playlists
+    pub privacy:                Option<Privacy>,
+    /// Text appended to the description of created playlists
+    pub description_footer:     Option<String>,
+    /// Tags of created YouTube playlists
+    pub tags:                   Option<Vec<String>>,
+    /// Language of the title and description of created playlists
+    pub default_language:       Option<String>,
+    /// Search strategies to try, in order
+    pub strategies:             Option<Vec<Strategy>>,
+    /// Minimum score of a match
+    pub threshold:              Option<f64>,
+    pub dedup:                  Option<Dedup>,
+    /// How to add tracks to YouTube, `data-api` or `innertube`
+    pub sink:                   Option<String>,
+    /// File containing the YouTube Music Cookie header, for the `innertube` sink
+    pub ytm_cookie_file:        Option<PathBuf>,
+    /// YouTube Data API quota units to use per day
+    pub quota_budget:           Option<u64>,
+    /// Days after which cached matches expire
+    pub cache_ttl:              Option<u64>,
+    /// Port of the redirect URI for logging in to Spotify
+    pub spotify_redirect_port:  Option<u16>,
+    pub rate_limits:            RateLimits
+}
+
+/// Requests per second to make at most to each API. `None` keeps the built-in limit
+#[derive(Clone, Copy, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct RateLimits {
+    pub spotify:    Option<NonZeroU32>,
+    /// The YouTube Data API
+    pub youtube:    Option<NonZeroU32>,
+    /// The YouTube Music search
+    pub search:     Option<NonZeroU32>,
+    /// The YouTube Music internal API
+    pub innertube:  Option<NonZeroU32>
+}
+
+impl Env {
+    /// The default location of the config file
+    pub fn default_path() -> Result<PathBuf> {
+        let dir = dirs::config_dir().ok_or(Error::NoDirectory("config"))?;
+        Ok(dir.join(env!("CARGO_PKG_NAME")).join("config.toml"))
+    }
+
+    /// Load the profile `profile` from the config file at `path`. Without a `profile`, the file's `default_profile` is used,
+    /// or only the top level settings if it has none. A missing file is treated as empty
+    ///
+    /// ## Errors
+    /// - IO errors
+    /// - If the file is not a valid config file
+    /// - If the file has no profile `profile`
+    pub fn load(path: &Path, profile: Option<&str>) -> Result<Self> {
+        let contents = if path.exists() { fs::read_to_string(path)? } else { String::new() };
+        Self::parse(&contents, profile)
+    }
+
+    /// Load the profile `profile` from the contents of a config file, like [Env::load]
+    fn parse(contents: &str, profile: Option<&str>) -> Result<Self> {
+        let mut root = toml::from_str::<toml::value::Table>(contents).map_err(|e| Error::Config(e.to_string()))?;
+
+        let mut profiles = match root.remove("profiles") {
+            Some(toml::Value::Table(profiles)) => profiles,
+            Some(_) => return Err(Error::Config("'profiles' must be a table".to_string())),
+            None => toml::value::Table::new()
+        };
+        let default_profile = match root.remove("default_profile") {
+            Some(toml::Value::String(name)) => Some(name),
+            Some(_) => return Err(Error::Config("'default_profile' must be a string".to_string())),
+            None => None
+        };
+
+        if let Some(name) = profile.map(str::to_string).or(default_profile) {
+            match profiles.remove(&name) {
+                Some(toml::Value::Table(overrides)) => merge(&mut root, overrides),
+                Some(_) => return Err(Error::Config(format!("profile '{}' must be a table", name))),
+                None => return Err(Error::invalid_value("profile", &name))
+            }
+        }
+
+        toml::Value::Table(root).try_into().map_err(|e: toml::de::Error| Error::Config(e.to_string()))
+    }
+}
+
+/// Set every key of `overrides` in `base`, merging tables with the tables they replace
+fn merge(base: &mut toml::value::Table, overrides: toml::value::Table) {
+    for (key, value) in overrides {
+        match (base.get_mut(&key), value) {
+            (Some(toml::Value::Table(base)), toml::Value::Table(value)) => merge(base, value),
+            (_, value) => {
+                base.insert(key, value);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CONFIG: &str = r#"
+default_profile = "work"
+spotify_client_id = "spotify"
+google_client_id = "personal"
+threshold = 0.7
+
+[rate_limits]
+spotify = 5
+youtube = 2
+
+[profiles.work]
+google_client_id = "work"
+privacy = "unlisted"
+
+[profiles.work.rate_limits]
+youtube = 10
+
+[profiles.empty]
+"#;
+
+    #[test]
+    fn default_profile() {
+        let env = Env::parse(CONFIG, None).unwrap();
+        assert_eq!(env.spotify_client_id, "spotify");
+        assert_eq!(env.google_client_id, "work");
+        assert_eq!(env.privacy, Some(Privacy::Unlisted));
+        assert_eq!(env.threshold, Some(0.7));
+
+        // Tables are merged key by key
+        assert_eq!(env.rate_limits.spotify, NonZeroU32::new(5));
+        assert_eq!(env.rate_limits.youtube, NonZeroU32::new(10));
+        assert_eq!(env.rate_limits.search, None);
+    }
+
+    #[test]
+    fn selected_profile() {
+        let env = Env::parse(CONFIG, Some("empty")).unwrap();
+        assert_eq!(env.google_client_id, "personal");
+        assert_eq!(env.privacy, None);
+        assert_eq!(env.rate_limits.youtube, NonZeroU32::new(2));
+    }
+
+    #[test]
+    fn top_level_only() {
+        let env = Env::parse("spotify_client_id = \"spotify\"\n[profiles.work]\nprivacy = \"public\"\n", None).unwrap();
+        assert_eq!(env.spotify_client_id, "spotify");
+        assert_eq!(env.privacy, None);
+
+        let env = Env::parse("", None).unwrap();
+        assert!(env.google_client_id.is_empty());
+    }
+
+    #[test]
+    fn invalid() {
+        assert!(matches!(Env::parse(CONFIG, Some("home")), Err(Error::InvalidValue { .. })));
+        assert!(matches!(Env::parse("default_profile = \"home\"", None), Err(Error::InvalidValue { .. })));
+        assert!(matches!(Env::parse("profiles = 1", None), Err(Error::Config(_))));
+        assert!(matches!(Env::parse("unknown = 1", None), Err(Error::Config(_))));
+        assert!(matches!(Env::parse("[profiles.work]\nthreshold = \"high\"", Some("work")), Err(Error::Config(_))));
+    }
+}
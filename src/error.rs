@@ -0,0 +1,56 @@
+use crate::api::{spotify, youtube};
+
+/// Errors returned by this crate
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    #[error(transparent)]
+    YouTube(#[from] youtube::Error),
+    #[error(transparent)]
+    Spotify(#[from] spotify::Error),
+    /// The YouTube Music internal API refused a request
+    #[error("YouTube Music: {0}")]
+    YouTubeMusic(String),
+    /// The YouTube Music search page was not in the expected format
+    #[error("Unable to read YouTube Music search results: {0}")]
+    Search(String),
+    /// Logging in to a service failed
+    #[error("Login failed: {0}")]
+    OAuth(String),
+    /// The platform has no directory of this kind to keep files in, e.g. `cache`
+    #[error("Unable to determine the {0} directory")]
+    NoDirectory(&'static str),
+    /// The config file is not valid
+    #[error("Invalid config file: {0}")]
+    Config(String),
+    /// A playlist file could not be read
+    #[error("Invalid playlist file: {0}")]
+    PlaylistFile(String),
+    /// A value given by the user could not be parsed
+    #[error("Invalid {kind} '{value}'")]
+    InvalidValue {
+        kind: &'static str,
+        value: String
+    },
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+    #[error(transparent)]
+    Json(#[from] serde_json::Error),
+    #[error(transparent)]
+    Csv(#[from] csv::Error),
+    #[error(transparent)]
+    Xml(#[from] quick_xml::Error),
+    #[error(transparent)]
+    Http(#[from] reqwest::Error)
+}
+
+impl Error {
+    pub(crate) fn invalid_value(kind: &'static str, value: &str) -> Self {
+        Self::InvalidValue {
+            kind,
+            value: value.to_string()
+        }
+    }
+}
+
+/// Result type of this crate
+pub type Result<T, E = Error> = std::result::Result<T, E>;
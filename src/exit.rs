@@ -0,0 +1,63 @@
+use sp2ytm::Error;
+use sp2ytm::api::{spotify, youtube};
+
+/// Invalid or missing arguments
+pub const USAGE: i32 = 2;
+/// Logging in failed, or a service refused the credentials or token
+pub const AUTH: i32 = 3;
+/// The YouTube quota or a rate limit is used up. Retrying later may succeed
+pub const QUOTA: i32 = 4;
+/// A service could not be reached or returned an error
+pub const SERVICE: i32 = 5;
+/// A local file, such as the config file or a job file, could not be read or written
+pub const DATA: i32 = 6;
+/// The conversion finished, but some tracks or playlist changes failed. Resuming retries them.
+/// Also used when some tracks given to `resolve` were not found
+pub const INCOMPLETE: i32 = 7;
+
+/// Why a command failed, and the exit code to report it with
+pub struct Failure {
+    pub code: i32,
+    pub message: String
+}
+
+impl Failure {
+    pub fn new(code: i32, message: impl Into<String>) -> Self {
+        Self {
+            code,
+            message: message.into()
+        }
+    }
+
+    pub fn usage(message: impl Into<String>) -> Self {
+        Self::new(USAGE, message)
+    }
+}
+
+/// Turn errors into a [Failure] describing what was being done
+pub trait Context<T> {
+    fn context(self, what: &str) -> Result<T, Failure>;
+}
+
+impl<T, E: Into<Error>> Context<T> for Result<T, E> {
+    fn context(self, what: &str) -> Result<T, Failure> {
+        self.map_err(|e| {
+            let e = e.into();
+            Failure::new(code(&e), format!("{}: {}", what, e))
+        })
+    }
+}
+
+/// The exit code of the class `e` belongs to
+fn code(e: &Error) -> i32 {
+    match e {
+        Error::YouTube(youtube::Error::Unauthorized(_))
+            | Error::Spotify(spotify::Error::InvalidClient(_) | spotify::Error::Unauthorized(_))
+            | Error::OAuth(_) => AUTH,
+        Error::YouTube(youtube::Error::QuotaExceeded | youtube::Error::QuotaBudgetExhausted(_) | youtube::Error::RateLimitExceeded)
+            | Error::Spotify(spotify::Error::RateLimitExceeded) => QUOTA,
+        Error::YouTube(_) | Error::Spotify(_) | Error::YouTubeMusic(_) | Error::Search(_) | Error::Http(_) => SERVICE,
+        Error::InvalidValue { .. } => USAGE,
+        Error::Io(_) | Error::Json(_) | Error::Csv(_) | Error::Xml(_) | Error::PlaylistFile(_) | Error::Config(_) | Error::NoDirectory(_) => DATA
+    }
+}
@@ -0,0 +1,191 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use crate::{Error, Result};
+use serde::{Serialize, Deserialize};
+use crate::service::{Playlist, PlaylistTrack};
+use crate::matcher::Match;
+use crate::dedup::Dedup;
+
+/// Where a track is in the conversion process
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Status {
+    /// Not resolved yet
+    Pending,
+    /// Resolved to an item of the target service, but not in the target playlist yet
+    Matched,
+    /// In the target playlist
+    Inserted,
+    /// Left out on purpose
+    Skipped,
+    /// No video was found
+    NotFound,
+    /// Resolved to the same item as an earlier track, see the track's `duplicate_of`
+    Duplicate,
+    /// Resolving or inserting failed, see the track's `error`
+    Failed
+}
+
+/// Which way a playlist is converted
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Direction {
+    #[default]
+    SpotifyToYouTube,
+    YouTubeToSpotify
+}
+
+impl Direction {
+    /// Link to the item `id` of the target service
+    pub fn target_item_url(&self, id: &str) -> String {
+        match self {
+            Self::SpotifyToYouTube => format!("https://music.youtube.com/watch?v={}", id),
+            Self::YouTubeToSpotify => format!("https://open.spotify.com/track/{}", id)
+        }
+    }
+
+    /// Link to the playlist `id` of the target service
+    pub fn target_playlist_url(&self, id: &str) -> String {
+        match self {
+            Self::SpotifyToYouTube => format!("https://music.youtube.com/playlist?list={}", id),
+            Self::YouTubeToSpotify => format!("https://open.spotify.com/playlist/{}", id)
+        }
+    }
+
+    /// Name of the target service
+    pub fn target_name(&self) -> &'static str {
+        match self {
+            Self::SpotifyToYouTube => "YouTube",
+            Self::YouTubeToSpotify => "Spotify"
+        }
+    }
+}
+
+/// A track and its progress
+#[derive(Serialize, Deserialize)]
+pub struct JobTrack {
+    pub track: PlaylistTrack,
+    pub status: Status,
+    /// The item of the target service the track resolved to
+    #[serde(rename = "match")]
+    pub found: Option<Match>,
+    pub error: Option<String>,
+    /// Index of the track this track is a duplicate of
+    #[serde(default)]
+    pub duplicate_of: Option<usize>
+}
+
+/// The playlist being converted
+#[derive(Serialize, Deserialize)]
+pub struct Source {
+    #[serde(alias = "spotify_playlist_id")]
+    pub playlist_id: String,
+    pub name: String,
+    pub description: Option<String>,
+    pub url: String
+}
+
+/// A conversion, persisted after every step so it can be resumed when interrupted
+#[derive(Serialize, Deserialize)]
+pub struct Job {
+    /// `None` for detached jobs, which are never saved
+    #[serde(skip)]
+    path: Option<PathBuf>,
+    #[serde(default)]
+    pub direction: Direction,
+    pub source: Source,
+    /// The playlist to add the tracks to. `None` until the playlist is created
+    #[serde(alias = "youtube_playlist_id")]
+    pub target_playlist_id: Option<String>,
+    /// Remove items from the target playlist which aren't in the source playlist
+    pub remove_missing: bool,
+    #[serde(default)]
+    pub dedup: Dedup,
+    /// Name of the stored Google account the conversion logs in with. `None` logs in without storing the account
+    #[serde(default)]
+    pub account: Option<String>,
+    pub tracks: Vec<JobTrack>
+}
+
+impl Job {
+    /// The default location of the job file for the source playlist `playlist_id`
+    pub fn default_path(playlist_id: &str) -> Result<PathBuf> {
+        let dir = dirs::data_dir().ok_or(Error::NoDirectory("data"))?;
+        Ok(dir.join(env!("CARGO_PKG_NAME")).join("jobs").join(format!("{}.json", playlist_id)))
+    }
+
+    /// Create a job converting `playlist`, with every track pending
+    pub fn new(path: &Path, direction: Direction, playlist_id: &str, playlist: Playlist, target_playlist_id: Option<String>, remove_missing: bool, dedup: Dedup) -> Self {
+        Self {
+            path: Some(path.to_path_buf()),
+            direction,
+            source: Source {
+                playlist_id: playlist_id.to_string(),
+                name: playlist.name,
+                description: playlist.description,
+                url: playlist.url
+            },
+            target_playlist_id,
+            remove_missing,
+            dedup,
+            account: None,
+            tracks: playlist.tracks
+                .into_iter()
+                .map(|track| JobTrack {
+                    track,
+                    status: Status::Pending,
+                    found: None,
+                    error: None,
+                    duplicate_of: None
+                })
+                .collect()
+        }
+    }
+
+    /// Load the job stored at `path`
+    ///
+    /// ## Errors
+    /// - IO errors
+    /// - If the file is not a valid job file
+    pub fn load(path: &Path) -> Result<Self> {
+        let mut job: Self = serde_json::from_str(&fs::read_to_string(path)?)?;
+        job.path = Some(path.to_path_buf());
+        Ok(job)
+    }
+
+    /// Whether every track was added or left out, so there is nothing left to resume
+    pub fn is_complete(&self) -> bool {
+        !self.tracks.iter().any(|t| matches!(t.status, Status::Pending | Status::Matched | Status::Failed))
+    }
+
+    /// The location the job is saved to
+    pub fn path(&self) -> Option<&Path> {
+        self.path.as_deref()
+    }
+
+    /// Keep the job in memory only from now on, so the file it was loaded from is left untouched
+    pub fn detach(&mut self) {
+        self.path = None;
+    }
+
+    /// Save the job. Does nothing for detached jobs
+    ///
+    /// ## Errors
+    /// - IO errors
+    pub fn save(&self) -> Result<()> {
+        let path = match &self.path {
+            Some(p) => p,
+            None => return Ok(())
+        };
+
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+
+        // Write to a temporary file first, so being interrupted while saving never leaves a corrupt job behind
+        let tmp = path.with_extension("json.tmp");
+        fs::write(&tmp, serde_json::to_string_pretty(self)?)?;
+        fs::rename(&tmp, path)?;
+        Ok(())
+    }
+}
@@ -0,0 +1,72 @@
+//! Convert playlists between Spotify and YouTube (Music).
+//!
+//! A conversion reads a playlist through a [service::PlaylistSource], resolves every track to an item of the
+//! target service with a [resolver::Resolver] and writes the result through a [service::PlaylistSink].
+//! Progress is kept in a [job::Job], so an interrupted conversion can be resumed:
+//!
+//! ```no_run
+//! use sp2ytm::{convert, job, matcher, overrides, playlists, resolver, service, Result};
+//! use sp2ytm::service::PlaylistSource;
+//!
+//! fn convert(env: &sp2ytm::env::Env, google_auth: String) -> Result<()> {
+//!     let source = service::spotify::SpotifySource { env };
+//!     let playlist = source.fetch_playlist("37i9dQZF1DXcBWIGoYBM5M")?;
+//!     let path = job::Job::default_path("37i9dQZF1DXcBWIGoYBM5M")?;
+//!     let mut job = job::Job::new(&path, job::Direction::SpotifyToYouTube, "37i9dQZF1DXcBWIGoYBM5M", playlist, None, false, Default::default());
+//!
+//!     let mut resolver = resolver::Resolver {
+//!         options: matcher::MatchOptions::default(),
+//!         overrides: overrides::Overrides::load(&overrides::Overrides::default_path(job.direction)?)?,
+//!         cache: None,
+//!         review: None
+//!     };
+//!     let mut map = playlists::PlaylistMap::load(&playlists::PlaylistMap::default_path()?)?;
+//!     let options = convert::PlaylistOptions { privacy: None, footer: None, tags: Vec::new(), default_language: None };
+//!     let sink = service::youtube::DataApiSink { auth: google_auth };
+//!     convert::run(&mut job, &mut resolver, &mut map, &options, &sink)?;
+//!     Ok(())
+//! }
+//! ```
+
+#[macro_use]
+extern crate lazy_static;
+
+/// Clients of the Spotify and YouTube APIs
+pub mod api;
+/// Logging in to Google and Spotify with OAuth2
+pub mod oauth2;
+/// Google accounts logged in to before, by name
+pub mod accounts;
+/// Application credentials
+pub mod env;
+/// Finding the item of the target service best matching a track
+pub mod matcher;
+/// The on-disk cache of earlier matches
+pub mod cache;
+/// Matches chosen by the user, which take precedence over searching
+pub mod overrides;
+/// Resolving tracks using the overrides, the cache, searching and interactive review
+pub mod resolver;
+/// Letting the user pick a match interactively
+pub mod review;
+/// Planning the changes which turn a YouTube playlist into the desired one
+pub mod sync;
+/// Which target playlist each source playlist was converted to
+pub mod playlists;
+/// Resumable conversions
+pub mod job;
+/// The conversion pipeline
+pub mod convert;
+/// Leaving duplicate matches out of the target playlist
+pub mod dedup;
+/// Reports on the outcome of a conversion
+pub mod report;
+/// Exporting the items tracks resolved to
+pub mod mapping;
+/// Playlist sources and targets
+pub mod service;
+/// Reading and writing playlist files
+pub mod playlist_file;
+mod error;
+
+pub use error::{Error, Result};
@@ -0,0 +1,100 @@
+use std::fs;
+use std::path::Path;
+use serde::Serialize;
+use crate::{Error, Result};
+use crate::job::{Job, Status};
+
+/// A source track and the item it resolved to
+#[derive(Serialize)]
+struct Entry<'a> {
+    title: &'a str,
+    artists: &'a [String],
+    spotify_uri: Option<String>,
+    isrc: Option<&'a str>,
+    /// ID of the item of the target service
+    id: &'a str,
+    url: String
+}
+
+#[derive(Serialize)]
+struct Mapping<'a> {
+    name: &'a str,
+    source_url: &'a str,
+    target_service: &'static str,
+    tracks: Vec<Entry<'a>>
+}
+
+/// Write the items the tracks of `job` resolved to to `path`, without needing the target playlist.
+/// The format is picked by the extension:
+/// - `.m3u8` or `.m3u`: the playlist as it would be in the target service, as links other players can open
+/// - `.json`: the item every matched source track resolved to, including duplicates
+///
+/// ## Errors
+/// - If the extension is not one of the above
+/// - IO errors
+pub fn write(job: &Job, path: &Path) -> Result<()> {
+    let contents = match path.extension().and_then(|e| e.to_str()).map(str::to_lowercase).as_deref() {
+        Some("m3u8" | "m3u") => to_m3u(job),
+        Some("json") => serde_json::to_string_pretty(&build(job))?,
+        _ => return Err(Error::invalid_value("mapping format, use a .m3u8 or .json file", &path.display().to_string()))
+    };
+
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+
+    fs::write(path, contents)?;
+    Ok(())
+}
+
+fn build(job: &Job) -> Mapping<'_> {
+    let tracks = job.tracks.iter()
+        .filter(|t| matches!(t.status, Status::Matched | Status::Inserted | Status::Duplicate))
+        .filter_map(|t| t.found.as_ref().map(|m| (t, m)))
+        .map(|(t, m)| Entry {
+            title: &t.track.name,
+            artists: &t.track.artists,
+            spotify_uri: t.track.id.as_ref().map(|id| format!("spotify:track:{}", id)),
+            isrc: t.track.isrc.as_deref(),
+            id: &m.candidate.id,
+            url: job.direction.target_item_url(&m.candidate.id)
+        })
+        .collect();
+
+    Mapping {
+        name: &job.source.name,
+        source_url: &job.source.url,
+        target_service: job.direction.target_name(),
+        tracks
+    }
+}
+
+/// An extended M3U playlist of the matched items, described by their own title and artists, or by the source track's if the service didn't name them
+fn to_m3u(job: &Job) -> String {
+    let mut out = format!("#EXTM3U\n#PLAYLIST:{}\n", job.source.name.replace(['\r', '\n'], " "));
+    for t in job.tracks.iter().filter(|t| matches!(t.status, Status::Matched | Status::Inserted)) {
+        let m = match &t.found {
+            Some(m) => m,
+            None => continue
+        };
+
+        let (title, artists) = if m.candidate.title.is_empty() {
+            (&t.track.name, &t.track.artists)
+        } else {
+            (&m.candidate.title, &m.candidate.artists)
+        };
+        let title = if artists.is_empty() {
+            title.clone()
+        } else {
+            format!("{} - {}", artists.join(", "), title)
+        };
+        let seconds = m.candidate.duration_ms
+            .or(t.track.duration_ms)
+            .map(|d| (d as f64 / 1000.0).round() as i64)
+            .unwrap_or(-1);
+
+        out.push_str(&format!("#EXTINF:{},{}\n{}\n", seconds, title.replace(['\r', '\n'], " "), job.direction.target_item_url(&m.candidate.id)));
+    }
+
+    out
+}
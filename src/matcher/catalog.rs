@@ -0,0 +1,24 @@
+use crate::Result;
+use crate::service::PlaylistTrack;
+use crate::matcher::{Candidate, Strategy};
+
+/// A service tracks can be matched against
+pub trait Catalog {
+    /// What the service calls the items it contains, e.g. `video`
+    fn item_name(&self) -> &'static str;
+
+    /// Build the search query for `track` using `strategy`, in the service's query syntax.
+    /// Returns `None` if the track lacks the information the strategy needs
+    fn query(&self, strategy: Strategy, track: &PlaylistTrack) -> Option<String> {
+        strategy.query(track)
+    }
+
+    /// Search the service, returning all results in the order the service ranked them
+    ///
+    /// ## Errors
+    /// - When the search request fails
+    fn search(&self, query: &str) -> Result<Vec<Candidate>>;
+
+    /// Extract the ID of an item from an ID or URL entered by the user
+    fn parse_id(&self, input: &str) -> Option<String>;
+}
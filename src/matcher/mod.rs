@@ -0,0 +1,130 @@
+use crate::Result;
+use log::{debug, trace};
+use serde::{Serialize, Deserialize};
+use crate::service::PlaylistTrack;
+
+mod catalog;
+mod normalize;
+mod score;
+mod strategy;
+
+pub use catalog::Catalog;
+pub use strategy::Strategy;
+
+/// A search result which may be the track we're looking for
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Candidate {
+    /// The ID of the result in the service it was found in, e.g. a YouTube video ID or Spotify track ID
+    pub id: String,
+    pub title: String,
+    pub artists: Vec<String>,
+    pub album: Option<String>,
+    pub duration_ms: Option<u64>
+}
+
+/// A candidate which passed the match threshold
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Match {
+    pub candidate: Candidate,
+    /// Score between 0.0 and 1.0
+    pub score: f64,
+    /// The strategy whose query produced the candidate
+    pub strategy: Strategy
+}
+
+impl Match {
+    /// A match chosen by the user, about which we know nothing but the ID
+    pub fn manual(id: &str) -> Self {
+        Self {
+            candidate: Candidate {
+                id: id.to_string(),
+                title: String::new(),
+                artists: Vec::new(),
+                album: None,
+                duration_ms: None
+            },
+            score: 1.0,
+            strategy: Strategy::Manual
+        }
+    }
+}
+
+/// Options controlling how tracks are matched
+#[derive(Clone)]
+pub struct MatchOptions {
+    /// Strategies to try, in order, until one produces a candidate passing the threshold
+    pub strategies: Vec<Strategy>,
+    /// Minimum score a candidate needs to be accepted, between 0.0 and 1.0
+    pub threshold: f64
+}
+
+impl Default for MatchOptions {
+    fn default() -> Self {
+        Self {
+            strategies: Strategy::ALL.to_vec(),
+            threshold: 0.6
+        }
+    }
+}
+
+/// The result of matching a track
+pub enum Outcome {
+    Matched(Match),
+    /// No candidate passed the threshold. Contains every candidate found, best first
+    Unmatched(Vec<Match>)
+}
+
+/// Find `track` in `catalog`, trying every configured strategy in order
+/// until one produces a candidate passing the threshold
+///
+/// ## Errors
+/// - When a search request fails
+pub fn find_match(track: &PlaylistTrack, options: &MatchOptions, catalog: &dyn Catalog) -> Result<Outcome> {
+    let mut rejected: Vec<Match> = Vec::new();
+
+    for &strategy in &options.strategies {
+        let query = match catalog.query(strategy, track) {
+            Some(q) => q,
+            None => {
+                trace!("Strategy '{}' is not applicable to '{}'", strategy, track.name);
+                continue;
+            }
+        };
+
+        debug!("Searching with strategy '{}': '{}'", strategy, query);
+        let mut ranked = rank(track, catalog.search(&query)?, strategy);
+        if ranked.is_empty() {
+            debug!("Strategy '{}' returned no candidates", strategy);
+            continue;
+        }
+
+        let best = ranked.remove(0);
+        if best.score >= options.threshold {
+            return Ok(Outcome::Matched(best));
+        }
+
+        debug!("Best candidate '{}' ({}) scored {:.2}, below threshold", best.candidate.title, best.candidate.id, best.score);
+        for m in std::iter::once(best).chain(ranked) {
+            if !rejected.iter().any(|r| r.candidate.id == m.candidate.id) {
+                rejected.push(m);
+            }
+        }
+    }
+
+    rejected.sort_by(|a, b| b.score.total_cmp(&a.score));
+    Ok(Outcome::Unmatched(rejected))
+}
+
+/// Score every candidate against `track`, best first
+pub fn rank(track: &PlaylistTrack, candidates: Vec<Candidate>, strategy: Strategy) -> Vec<Match> {
+    let mut ranked: Vec<_> = candidates.into_iter()
+        .map(|candidate| Match {
+            score: score::score(track, &candidate),
+            candidate,
+            strategy
+        })
+        .collect();
+
+    ranked.sort_by(|a, b| b.score.total_cmp(&a.score));
+    ranked
+}
@@ -0,0 +1,89 @@
+use unicode_normalization::UnicodeNormalization;
+use unicode_normalization::char::is_combining_mark;
+
+/// Characters below this code point belong to the Latin scripts.
+/// Combining marks are only stripped from these, as in e.g. Japanese (が → か) or Devanagari they change the letter itself
+const LATIN_END: u32 = 0x0250;
+
+/// Apply compatibility normalization, turning e.g. full-width `（ＡＢＣ）` into `(ABC)`
+pub fn compat(s: &str) -> String {
+    s.nfkc().collect()
+}
+
+/// Fold `s` into the form used for comparison:
+/// compatibility normalized, case folded and with diacritics removed from Latin letters
+pub fn fold(s: &str) -> String {
+    let mut base_is_latin = false;
+    let stripped: String = compat(s)
+        .to_lowercase()
+        .nfd()
+        .filter(|&c| {
+            if is_combining_mark(c) {
+                return !base_is_latin;
+            }
+
+            base_is_latin = (c as u32) < LATIN_END;
+            true
+        })
+        .collect();
+
+    stripped.nfc().collect()
+}
+
+/// Transliterate `s` into lowercase ASCII, e.g. `Кино` into `kino` or `ロック` into `rotuku`.
+/// Returns `None` if `s` is already ASCII, as transliterating it would not change anything
+pub fn transliterate(s: &str) -> Option<String> {
+    if s.is_ascii() {
+        return None;
+    }
+
+    Some(deunicode::deunicode(&fold(s)).to_lowercase())
+}
+
+/// Whether `c` belongs to a script which doesn't separate words with spaces
+pub fn is_unspaced(c: char) -> bool {
+    matches!(c as u32,
+        0x3040..=0x30FF     // Hiragana and Katakana
+        | 0x3400..=0x4DBF   // CJK Unified Ideographs Extension A
+        | 0x4E00..=0x9FFF   // CJK Unified Ideographs
+        | 0xF900..=0xFAFF   // CJK Compatibility Ideographs
+        | 0x0E00..=0x0E7F   // Thai
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fold_full_width() {
+        assert_eq!(fold("ＡＢＣ（Ｌｉｖｅ）"), "abc(live)");
+    }
+
+    #[test]
+    fn fold_strips_latin_diacritics() {
+        assert_eq!(fold("Beyoncé Ñandú"), "beyonce nandu");
+        assert_eq!(fold("Ｃａｆé"), "cafe");
+    }
+
+    #[test]
+    fn fold_keeps_kana_voicing_marks() {
+        assert_eq!(fold("がぎぐ"), "がぎぐ");
+        assert_eq!(fold("パピプ"), "パピプ");
+        // Half-width kana with a separate voicing mark compose into the full-width letter
+        assert_eq!(fold("ｶﾞ"), "ガ");
+    }
+
+    #[test]
+    fn transliterate_non_ascii() {
+        assert_eq!(transliterate("Кино").as_deref(), Some("kino"));
+        assert_eq!(transliterate("さくらんぼ").as_deref(), Some("sakuranbo"));
+        assert_eq!(transliterate("사랑해").as_deref(), Some("saranghae"));
+        assert_eq!(transliterate("Café").as_deref(), Some("cafe"));
+    }
+
+    #[test]
+    fn transliterate_ascii_is_none() {
+        assert_eq!(transliterate("Kino"), None);
+    }
+}
@@ -0,0 +1,168 @@
+use std::collections::HashSet;
+use regex::Regex;
+use crate::service::PlaylistTrack;
+use crate::matcher::Candidate;
+use crate::matcher::normalize;
+
+lazy_static! {
+    /// Bracketed featured artist credits, e.g. `(feat. Someone)` or `[with Someone]`
+    static ref FEATURING: Regex = Regex::new(r"(?i)\s*[(\[](feat\.?|ft\.?|featuring|with)\s[^)\]]*[)\]]").expect("Invalid FEATURING regex");
+    /// Version suffixes Spotify appends to titles, e.g. ` - Remastered 2011` or ` - Radio Edit`
+    static ref VERSION_SUFFIX: Regex = Regex::new(r"(?i)\s+-\s+[^-]*(remaster|version|edit|mono|stereo|mix|live|acoustic|demo)[^-]*$").expect("Invalid VERSION_SUFFIX regex");
+}
+
+/// Differences in duration up to this many milliseconds are considered a perfect match
+const DURATION_TOLERANCE_MS: f64 = 2_000.0;
+/// Differences in duration of this many milliseconds or more score zero
+const DURATION_CUTOFF_MS: f64 = 30_000.0;
+
+/// Strip featured artists and version suffixes from a title
+pub fn normalize_title(title: &str) -> String {
+    let title = normalize::compat(title);
+    let title = FEATURING.replace_all(&title, "");
+    VERSION_SUFFIX.replace(&title, "").trim().to_string()
+}
+
+/// Score how likely `candidate` is to be `track`, between 0.0 and 1.0
+pub fn score(track: &PlaylistTrack, candidate: &Candidate) -> f64 {
+    let title = similarity(&normalize_title(&track.name), &normalize_title(&candidate.title));
+
+    let artist = if candidate.artists.is_empty() {
+        // Without artist information the artist is often part of the title, e.g. 'Artist - Title'
+        track.artists.iter()
+            .map(|a| containment(a, &candidate.title))
+            .fold(0.0, f64::max)
+    } else {
+        track.artists.iter()
+            .flat_map(|a| candidate.artists.iter().map(move |b| similarity(a, b)))
+            .fold(0.0, f64::max)
+    };
+
+    // Title and artist are always weighed, duration and album only when both sides know them
+    let mut components = vec![(0.5, title), (0.3, artist)];
+    if let (Some(a), Some(b)) = (track.duration_ms, candidate.duration_ms) {
+        let diff = (a as f64 - b as f64).abs();
+        let duration = 1.0 - ((diff - DURATION_TOLERANCE_MS) / (DURATION_CUTOFF_MS - DURATION_TOLERANCE_MS)).clamp(0.0, 1.0);
+        components.push((0.2, duration));
+    }
+
+    if let (Some(a), Some(b)) = (&track.album, &candidate.album) {
+        components.push((0.1, similarity(a, b)));
+    }
+
+    let total_weight: f64 = components.iter().map(|(w, _)| w).sum();
+    components.iter().map(|(w, v)| w * v).sum::<f64>() / total_weight
+}
+
+/// Split a string into folded words. Scripts which don't separate words with spaces are split into single characters
+fn tokens(s: &str) -> HashSet<String> {
+    let folded = normalize::fold(s);
+    let mut tokens = HashSet::new();
+    let mut word = String::new();
+
+    for c in folded.chars() {
+        if normalize::is_unspaced(c) {
+            tokens.insert(c.to_string());
+        } else if c.is_alphanumeric() {
+            word.push(c);
+            continue;
+        }
+
+        if !word.is_empty() {
+            tokens.insert(std::mem::take(&mut word));
+        }
+    }
+
+    if !word.is_empty() {
+        tokens.insert(word);
+    }
+
+    tokens
+}
+
+/// Split a string into pairs of adjacent alphanumeric characters, ignoring word boundaries
+fn bigrams(s: &str) -> HashSet<String> {
+    let chars: Vec<char> = s.chars()
+        .filter(|c| c.is_alphanumeric())
+        .collect();
+
+    chars.windows(2)
+        .map(|w| w.iter().collect())
+        .collect()
+}
+
+/// Compare `a` and `b` with `f`, both as written and transliterated to ASCII, returning the best result.
+/// This lets e.g. a romanized title on one service match the native script title on the other.
+/// Transliterations rarely agree on spacing and vowel spelling with hand romanizations, so those are compared by character bigrams
+fn compare<F: Fn(&HashSet<String>, &HashSet<String>) -> f64>(a: &str, b: &str, f: F) -> f64 {
+    let native = f(&tokens(a), &tokens(b));
+    if a.is_ascii() && b.is_ascii() {
+        return native;
+    }
+
+    let a = normalize::transliterate(a).unwrap_or_else(|| normalize::fold(a));
+    let b = normalize::transliterate(b).unwrap_or_else(|| normalize::fold(b));
+    native.max(f(&bigrams(&a), &bigrams(&b)))
+}
+
+/// Dice coefficient of the words in `a` and `b`
+fn similarity(a: &str, b: &str) -> f64 {
+    compare(a, b, |a, b| {
+        if a.is_empty() || b.is_empty() {
+            return 0.0;
+        }
+
+        2.0 * a.intersection(b).count() as f64 / (a.len() + b.len()) as f64
+    })
+}
+
+/// Fraction of the words in `needle` which occur in `haystack`
+fn containment(needle: &str, haystack: &str) -> f64 {
+    compare(needle, haystack, |needle, haystack| {
+        if needle.is_empty() {
+            return 0.0;
+        }
+
+        needle.intersection(haystack).count() as f64 / needle.len() as f64
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn similarity_of_cyrillic_and_latin() {
+        assert_eq!(similarity("Кино", "Kino"), 1.0);
+        assert_eq!(similarity("Группа крови", "Gruppa krovi"), 1.0);
+        assert_eq!(similarity("Кино", "Group of blood"), 0.0);
+    }
+
+    #[test]
+    fn similarity_of_kana_and_romaji() {
+        assert_eq!(similarity("さくらんぼ", "Sakuranbo"), 1.0);
+        // Transliteration spells small tsu and kanji readings differently than hand romanization, but still overlaps
+        assert!(similarity("ロック", "Rokku") > 0.4);
+        assert!(similarity("残酷な天使のテーゼ", "Zankoku na Tenshi no Thesis") > 0.6);
+    }
+
+    #[test]
+    fn similarity_of_hangul_and_romanization() {
+        assert_eq!(similarity("사랑해", "Saranghae"), 1.0);
+        assert_eq!(similarity("봄날", "Bom Nal"), 1.0);
+        assert_eq!(similarity("봄날", "Spring Day"), 0.0);
+    }
+
+    #[test]
+    fn similarity_ignores_width_case_and_latin_diacritics() {
+        assert_eq!(similarity("ＡＢＣ", "abc"), 1.0);
+        assert_eq!(similarity("Café del Mar", "Cafe Del Mar"), 1.0);
+    }
+
+    #[test]
+    fn unspaced_scripts_are_split_into_characters() {
+        let tokens = tokens("残酷な天使");
+        assert_eq!(tokens.len(), 5);
+        assert!(tokens.contains("天"));
+    }
+}
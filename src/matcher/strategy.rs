@@ -0,0 +1,67 @@
+use std::fmt;
+use std::str::FromStr;
+use crate::Error;
+use serde::{Serialize, Deserialize};
+use crate::service::PlaylistTrack;
+use crate::matcher::score::normalize_title;
+
+/// A way of turning a track into a search query
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Strategy {
+    /// The full title and all artists
+    Full,
+    /// The title without version suffixes and featured artists, and the primary artist
+    Normalized,
+    /// The title and the album name
+    Album,
+    /// The track's ISRC
+    Isrc,
+    /// Chosen by the user rather than found by searching
+    Manual
+}
+
+impl Strategy {
+    /// All search strategies, in their default order
+    pub const ALL: [Strategy; 4] = [Strategy::Full, Strategy::Normalized, Strategy::Album, Strategy::Isrc];
+
+    /// The name of the strategy as used on the command line
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Full => "full",
+            Self::Normalized => "normalized",
+            Self::Album => "album",
+            Self::Isrc => "isrc",
+            Self::Manual => "manual"
+        }
+    }
+
+    /// Build the search query for `track`.
+    /// Returns `None` if the track lacks the information this strategy needs
+    pub fn query(&self, track: &PlaylistTrack) -> Option<String> {
+        match self {
+            Self::Full => Some(format!("{} {}", track.name, track.artists.join(" "))),
+            Self::Normalized => Some(format!("{} {}", normalize_title(&track.name), track.artist())),
+            Self::Album => track.album.as_ref().map(|album| format!("{} {}", track.name, album)),
+            Self::Isrc => track.isrc.clone(),
+            Self::Manual => None
+        }
+    }
+}
+
+impl fmt::Display for Strategy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl FromStr for Strategy {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::ALL.iter()
+            .find(|strategy| strategy.name() == s)
+            .copied()
+            .ok_or_else(|| Error::invalid_value("match strategy", s))
+    }
+}
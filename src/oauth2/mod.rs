@@ -1,6 +1,6 @@
 use std::sync::mpsc::{channel, Sender};
 use rand::Rng;
-use anyhow::Result;
+use crate::{Error, Result};
 use crate::env::Env;
 use crate::oauth2::server::start_actix;
 use log::{info, debug};
@@ -8,13 +8,85 @@ use serde::Serialize;
 
 mod server;
 mod port;
-mod google_api;
+mod token;
 
-pub fn do_oauth(env: Env) -> Result<String> {
+pub use token::Tokens;
+
+/// An OAuth2 authorization server we can log in to with the authorization code flow and PKCE
+pub struct Provider {
+    /// Name of the service, as shown to the user
+    name:           &'static str,
+
+    /// The authorization endpoint
+    auth_uri:       &'static str,
+
+    /// The token endpoint
+    token_uri:      &'static str,
+
+    /// The scopes requested
+    scope:          &'static str,
+
+    /// Application's client ID
+    client_id:      String,
+
+    /// Application's client secret, `None` for providers which only need the code verifier
+    client_secret:  Option<String>,
+
+    /// Host of the redirect URI, as registered with the provider
+    redirect_host:  &'static str,
+
+    /// Port of the redirect URI for providers which only accept registered redirect URIs exactly. `None` picks a free port
+    redirect_port:  Option<u16>,
+
+    /// Whether the provider only grants a refresh token when asked for offline access
+    offline_access: bool
+}
+
+impl Provider {
+    /// Google, with access to the user's YouTube account
+    pub fn google(env: &Env) -> Self {
+        Self {
+            name:           "Google",
+            auth_uri:       "https://accounts.google.com/o/oauth2/v2/auth",
+            token_uri:      "https://oauth2.googleapis.com/token",
+            scope:          "https://www.googleapis.com/auth/youtube",
+            client_id:      env.google_client_id.clone(),
+            client_secret:  Some(env.google_client_secret.clone()),
+            redirect_host:  "localhost",
+            redirect_port:  None,
+            offline_access: true
+        }
+    }
+
+    /// Spotify, with access to the user's playlists.
+    /// `http://127.0.0.1:<redirect_port>` must be registered as redirect URI of the application
+    pub fn spotify(env: &Env, redirect_port: u16) -> Self {
+        Self {
+            name:           "Spotify",
+            auth_uri:       "https://accounts.spotify.com/authorize",
+            token_uri:      "https://accounts.spotify.com/api/token",
+            scope:          "playlist-read-private playlist-modify-public playlist-modify-private",
+            client_id:      env.spotify_client_id.clone(),
+            client_secret:  None,
+            redirect_host:  "127.0.0.1",
+            redirect_port:  Some(redirect_port),
+            offline_access: false
+        }
+    }
+}
+
+/// Log in to `provider` in the user's browser, returning the granted tokens.
+/// Google always asks which account, or which of its brand accounts, to log in with
+///
+/// ## Errors
+/// - If the local web server receiving the redirect can't be started
+/// - If exchanging the authorization code fails
+pub fn do_oauth(provider: &Provider) -> Result<Tokens> {
     debug!("Generating code verified & challenge and state for OAuth2 login");
     let (verifier, challenge) = generate_code();
     let state: String = rand::thread_rng().sample_iter(rand::distributions::Alphanumeric).take(32).map(char::from).collect();
-    let port = get_port();
+    let port = provider.redirect_port.unwrap_or_else(get_port);
+    let redirect_uri = format!("http://{}:{}", provider.redirect_host, port);
 
     let (tx_endpoint, rx_endpoint) = channel();
     let actix_data = WebData {
@@ -32,25 +104,34 @@ pub fn do_oauth(env: Env) -> Result<String> {
     });
 
     debug!("Waiting for the Actix server to be started");
-    let actix_server = rx_actix.recv()?;
-    let auth_uri = create_authentication_uri(&env, &challenge, &state, &format!("http://localhost:{}", port));
+    let actix_server = rx_actix.recv().map_err(|_| Error::OAuth("the web server receiving the redirect failed to start".to_string()))?;
+    let auth_uri = create_authentication_uri(provider, &challenge, &state, &redirect_uri);
 
-    info!("Please open the following URL to log in: {}", &auth_uri);
+    info!("Please open the following URL to log in to {}: {}", provider.name, &auth_uri);
 
     debug!("Waiting for user to complete login flow");
-    let code = rx_endpoint.recv()?;
+    let code = rx_endpoint.recv().map_err(|_| Error::OAuth("the web server stopped before the login was completed".to_string()))?;
     debug!("User has completed the login flow");
 
     debug!("Stopping Actix web server");
     actix_web::rt::System::new("").block_on(actix_server.stop(true));
 
     debug!("Exchanging received code for access token");
-    let resp = google_api::exchange_access_token(&env, &code, &verifier, &format!("http://localhost:{}", port))?;
-    Ok(resp.access_token)
+    token::exchange_access_token(provider, &code, &verifier, &redirect_uri)
+}
+
+/// Get a new access token from `provider` with a refresh token of an earlier login, without involving the user
+///
+/// ## Errors
+/// - If the provider refuses the refresh token, e.g. because access was revoked
+pub fn refresh(provider: &Provider, refresh_token: &str) -> Result<String> {
+    debug!("Refreshing access token for {}", provider.name);
+    Ok(token::refresh_access_token(provider, refresh_token)?.access_token)
 }
 
+/// State shared with the handler of the redirect
 #[derive(Clone)]
-pub struct WebData {
+pub(crate) struct WebData {
     state: String,
     tx_endpoint: Sender<String>,
 }
@@ -65,7 +146,7 @@ fn generate_code() -> (String, String) {
             let mut hasher = sha2::Sha256::new();
             hasher.update(code_verifier.as_bytes());
             let digest = hasher.finalize();
-            base64::encode(digest.as_slice())
+            base64::encode(digest)
         };
 
         if code_challenge.contains('+') || code_challenge.contains('/') {
@@ -99,33 +180,40 @@ struct AuthenticationRequest<'a> {
 
     /// State parameter
     state:                  &'a str,
+
+    /// `offline` to be granted a refresh token
+    #[serde(skip_serializing_if = "Option::is_none")]
+    access_type:            Option<&'static str>,
+
+    /// Which pages to show the user even if they logged in before
+    #[serde(skip_serializing_if = "Option::is_none")]
+    prompt:                 Option<&'static str>,
 }
 
 /// Create an authentication URL used for step 1 in the OAuth2 flow
-pub fn create_authentication_uri(env: &Env, code_challenge: &str, state: &str, redirect_uri: &str) -> String {
+pub fn create_authentication_uri(provider: &Provider, code_challenge: &str, state: &str, redirect_uri: &str) -> String {
     let auth_request = AuthenticationRequest {
-        client_id:              &env.google_client_id,
+        client_id:              &provider.client_id,
         redirect_uri,
         response_type:          "code",
-        scope:                  "https://www.googleapis.com/auth/youtube",
-        code_challenge:         &code_challenge,
+        scope:                  provider.scope,
+        code_challenge,
         code_challenge_method:  "S256",
-        state:                  &state
+        state,
+        access_type:            provider.offline_access.then_some("offline"),
+        // Refresh tokens are only granted on consent, and picking the account is how a brand account's channel is chosen
+        prompt:                 provider.offline_access.then_some("select_account consent")
     };
 
     let qstring = serde_qs::to_string(&auth_request).unwrap();
-    format!("https://accounts.google.com/o/oauth2/v2/auth?{}", qstring)
+    format!("{}?{}", provider.auth_uri, qstring)
 }
 
 fn get_port() -> u16 {
-    let port = {
-        let mut port = rand::thread_rng().gen_range(4000..8000) as u16;
-        while !port::is_free(port) {
-            port = rand::thread_rng().gen_range(4000..8000) as u16;
-        }
-
-        port
-    };
+    let mut port = rand::thread_rng().gen_range(4000..8000) as u16;
+    while !port::is_free(port) {
+        port = rand::thread_rng().gen_range(4000..8000) as u16;
+    }
 
     port
 }
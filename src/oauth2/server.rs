@@ -1,11 +1,12 @@
 use std::sync::mpsc::Sender;
 use actix_web::{get, web, App, HttpServer, HttpResponse};
-use anyhow::Result;
+use crate::{Error, Result};
 use serde::Deserialize;
 use crate::oauth2::WebData;
 use log::{debug, error};
 
-pub fn start_actix(appdata: WebData, tx: Sender<actix_server::Server>, port: u16) -> Result<()> {
+/// Run the web server receiving the redirect on `port`, sending the server handle through `tx` once it runs
+pub(crate) fn start_actix(appdata: WebData, tx: Sender<actix_server::Server>, port: u16) -> Result<()> {
     debug!("Starting Actix system");
     let mut sys = actix_web::rt::System::new("sp2ytm");
     let actix = HttpServer::new(move || App::new()
@@ -15,7 +16,7 @@ pub fn start_actix(appdata: WebData, tx: Sender<actix_server::Server>, port: u16
     ).bind(format!("127.0.0.1:{}", port))?.run();
 
     debug!("Starting Actix server");
-    tx.send(actix.clone())?;
+    tx.send(actix.clone()).map_err(|_| Error::OAuth("the login flow stopped waiting for the web server".to_string()))?;
     sys.block_on(actix)?;
 
     Ok(())
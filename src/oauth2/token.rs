@@ -0,0 +1,111 @@
+use crate::oauth2::Provider;
+use crate::{Error, Result};
+use serde::{Serialize, Deserialize};
+
+/// Struct describing the request to exchange an access code for an access token
+#[derive(Serialize)]
+struct ExchangeAccessTokenRequest<'a> {
+    /// The application's client ID
+    client_id:          &'a str,
+
+    /// The application's client secret
+    #[serde(skip_serializing_if = "Option::is_none")]
+    client_secret:      Option<&'a str>,
+
+    /// The access code
+    code:               &'a str,
+
+    /// The verifier halve of the code challenge
+    code_verifier:      &'a str,
+
+    /// The grant type
+    grant_type:         &'static str,
+
+    /// The original redirect URI
+    redirect_uri:       &'a str
+}
+
+/// Struct describing the request to exchange a refresh token for a new access token
+#[derive(Serialize)]
+struct RefreshAccessTokenRequest<'a> {
+    /// The application's client ID
+    client_id:          &'a str,
+
+    /// The application's client secret
+    #[serde(skip_serializing_if = "Option::is_none")]
+    client_secret:      Option<&'a str>,
+
+    /// The refresh token
+    refresh_token:      &'a str,
+
+    /// The grant type
+    grant_type:         &'static str
+}
+
+/// The tokens granted by a provider
+#[derive(Deserialize)]
+pub struct Tokens {
+    /// The access token
+    pub access_token:   String,
+
+    /// The refresh token, if the provider granted one
+    #[serde(default)]
+    pub refresh_token:  Option<String>
+}
+
+/// Exchange an access code for an access token
+///
+/// ## Errors
+/// - Provider API error
+/// - Reqwest error
+pub fn exchange_access_token(provider: &Provider, access_token: &str, code_verifier: &str, redirect_uri: &str) -> Result<Tokens> {
+
+    //We can now exchange this token for a refresh_token and the likes
+    let exchange_request = ExchangeAccessTokenRequest {
+        client_id: &provider.client_id,
+        client_secret: provider.client_secret.as_deref(),
+        code: access_token,
+        code_verifier,
+        grant_type: "authorization_code",
+        redirect_uri
+    };
+
+    // Send a request to the provider to exchange the code for the necessary codes.
+    // Not every provider accepts JSON, but all accept a form
+    let response = reqwest::blocking::Client::new().post(provider.token_uri)
+        .form(&exchange_request)
+        .send()?;
+
+    if !response.status().is_success() {
+        return Err(Error::OAuth(format!("{} refused the authorization code: {}", provider.name, response.text()?)));
+    }
+
+    // Deserialize from JSON
+    let exchange_response: Tokens = response.json()?;
+
+    Ok(exchange_response)
+}
+
+/// Exchange a refresh token for a new access token
+///
+/// ## Errors
+/// - Provider API error
+/// - Reqwest error
+pub fn refresh_access_token(provider: &Provider, refresh_token: &str) -> Result<Tokens> {
+    let refresh_request = RefreshAccessTokenRequest {
+        client_id: &provider.client_id,
+        client_secret: provider.client_secret.as_deref(),
+        refresh_token,
+        grant_type: "refresh_token"
+    };
+
+    let response = reqwest::blocking::Client::new().post(provider.token_uri)
+        .form(&refresh_request)
+        .send()?;
+
+    if !response.status().is_success() {
+        return Err(Error::OAuth(format!("{} refused the refresh token: {}", provider.name, response.text()?)));
+    }
+
+    Ok(response.json()?)
+}
\ No newline at end of file
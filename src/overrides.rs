@@ -0,0 +1,192 @@
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::path::{Path, PathBuf};
+use crate::{Error, Result};
+use log::debug;
+use serde::{Serialize, Deserialize};
+use crate::service::{spotify, PlaylistTrack};
+use crate::job::Direction;
+
+/// What to do with a track instead of searching for it
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(from = "String", into = "String")]
+pub enum Override {
+    /// Use this YouTube video ID, or Spotify track ID when converting from YouTube
+    Video(String),
+    /// Leave the track out of the playlist
+    Skip
+}
+
+impl From<String> for Override {
+    fn from(s: String) -> Self {
+        if s.eq_ignore_ascii_case("skip") {
+            Self::Skip
+        } else {
+            Self::Video(s)
+        }
+    }
+}
+
+impl From<Override> for String {
+    fn from(o: Override) -> Self {
+        match o {
+            Override::Video(id) => id,
+            Override::Skip => "skip".to_string()
+        }
+    }
+}
+
+/// User maintained overrides, stored as a JSON object.
+/// Keys are either a Spotify track ID, URI or URL, or `title – artist`. Values are an ID in the target service or `skip`.
+/// Conversions in either direction keep their overrides in separate files, as the IDs of one service mean nothing to the other
+pub struct Overrides {
+    path: PathBuf,
+    /// The entries as written by the user, so we can save them without rewriting their keys
+    raw: BTreeMap<String, Override>,
+    /// The entries by canonical key
+    entries: HashMap<String, Override>
+}
+
+impl Overrides {
+    /// The default location of the overrides file for conversions in `direction`
+    pub fn default_path(direction: Direction) -> Result<PathBuf> {
+        let dir = dirs::config_dir().ok_or(Error::NoDirectory("config"))?;
+        let name = match direction {
+            Direction::SpotifyToYouTube => "overrides.json",
+            Direction::YouTubeToSpotify => "overrides-spotify.json"
+        };
+
+        Ok(dir.join(env!("CARGO_PKG_NAME")).join(name))
+    }
+
+    /// Load the overrides stored at `path`. A missing file is treated as having no overrides
+    ///
+    /// ## Errors
+    /// - IO errors
+    /// - If the file is not a valid overrides file
+    pub fn load(path: &Path) -> Result<Self> {
+        let raw: BTreeMap<String, Override> = if path.exists() {
+            serde_json::from_str(&fs::read_to_string(path)?)?
+        } else {
+            debug!("No overrides file at {:?}", path);
+            BTreeMap::new()
+        };
+
+        let overrides = Self::new(path, raw);
+        debug!("Loaded {} overrides", overrides.entries.len());
+        Ok(overrides)
+    }
+
+    /// The overrides `raw`, saved to `path`
+    fn new(path: &Path, raw: BTreeMap<String, Override>) -> Self {
+        let entries = raw.iter()
+            .map(|(k, v)| (canonical_key(k), v.clone()))
+            .collect::<HashMap<_, _>>();
+
+        Self {
+            path: path.to_path_buf(),
+            raw,
+            entries
+        }
+    }
+
+    /// Get the override for `track`, preferring one keyed by Spotify track ID over one keyed by title and artist
+    pub fn get(&self, track: &PlaylistTrack) -> Option<&Override> {
+        track.id.as_ref()
+            .and_then(|id| self.entries.get(id))
+            .or_else(|| self.entries.get(&canonical_key(&title_key(track))))
+    }
+
+    /// Set the override for `track` and save the overrides file.
+    /// The override is keyed by Spotify track ID, or by title and artist for local files
+    ///
+    /// ## Errors
+    /// - IO errors
+    pub fn set(&mut self, track: &PlaylistTrack, value: Override) -> Result<()> {
+        let key = track.id.clone().unwrap_or_else(|| title_key(track));
+        let canonical = canonical_key(&key);
+
+        // Drop entries written differently which refer to the same track, e.g. by Spotify URI
+        self.raw.retain(|k, _| canonical_key(k) != canonical);
+        self.raw.insert(key, value.clone());
+        self.entries.insert(canonical, value);
+
+        if let Some(parent) = self.path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+
+        fs::write(&self.path, serde_json::to_string_pretty(&self.raw)?)?;
+        Ok(())
+    }
+}
+
+/// The `title – artist` key for `track`
+fn title_key(track: &PlaylistTrack) -> String {
+    format!("{} – {}", track.name, track.artist())
+}
+
+/// Reduce a key to the form it is looked up by: a bare Spotify track ID, or a lowercased `title – artist`.
+/// Both hyphens and en dashes are accepted as separator, so they are treated the same everywhere in the key
+fn canonical_key(key: &str) -> String {
+    let key = key.trim();
+    if let Some(id) = spotify::parse_track_id(key) {
+        return id.to_string();
+    }
+
+    key.to_lowercase().replace(" - ", " – ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn overrides(json: &str) -> Overrides {
+        Overrides::new(Path::new("overrides.json"), serde_json::from_str(json).unwrap())
+    }
+
+    fn track(id: Option<&str>) -> PlaylistTrack {
+        PlaylistTrack {
+            id: id.map(str::to_string),
+            name: "Never Gonna Give You Up".to_string(),
+            artists: vec!["Rick Astley".to_string()],
+            album: None,
+            isrc: None,
+            duration_ms: None
+        }
+    }
+
+    #[test]
+    fn spotify_keys() {
+        let expected = Some(Override::Video("dQw4w9WgXcQ".to_string()));
+        for key in [
+            "4uLU6hMCjMI75M1A2tKUQC",
+            "spotify:track:4uLU6hMCjMI75M1A2tKUQC",
+            "https://open.spotify.com/intl-de/track/4uLU6hMCjMI75M1A2tKUQC?si=1a2b3c"
+        ] {
+            let overrides = overrides(&format!(r#"{{"{}": "dQw4w9WgXcQ"}}"#, key));
+            assert_eq!(overrides.get(&track(Some("4uLU6hMCjMI75M1A2tKUQC"))), expected.as_ref(), "{}", key);
+            assert_eq!(overrides.get(&track(None)), None, "{}", key);
+        }
+    }
+
+    #[test]
+    fn title_keys() {
+        for key in ["Never Gonna Give You Up - Rick Astley", "never gonna give you up – rick astley"] {
+            let overrides = overrides(&format!(r#"{{"{}": "SKIP"}}"#, key));
+            assert_eq!(overrides.get(&track(None)), Some(&Override::Skip), "{}", key);
+            // A track on Spotify is found by title too, if it has no override by ID
+            assert_eq!(overrides.get(&track(Some("4uLU6hMCjMI75M1A2tKUQC"))), Some(&Override::Skip), "{}", key);
+        }
+    }
+
+    #[test]
+    fn id_before_title() {
+        let overrides = overrides(r#"{
+            "Never Gonna Give You Up – Rick Astley": "skip",
+            "spotify:track:4uLU6hMCjMI75M1A2tKUQC": "dQw4w9WgXcQ"
+        }"#);
+
+        assert_eq!(overrides.get(&track(Some("4uLU6hMCjMI75M1A2tKUQC"))), Some(&Override::Video("dQw4w9WgXcQ".to_string())));
+        assert_eq!(overrides.get(&track(None)), Some(&Override::Skip));
+    }
+}
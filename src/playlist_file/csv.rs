@@ -0,0 +1,182 @@
+use std::collections::HashMap;
+use crate::{Error, Result};
+use crate::service::{spotify, Playlist, PlaylistTrack};
+
+/// Header names of every column we read, lowercase. Includes the headers of both the current and older Exportify exports
+const TITLE: &[&str] = &["track name", "title", "name", "track"];
+const ARTISTS: &[&str] = &["artist name(s)", "artists", "artist"];
+const ALBUM: &[&str] = &["album name", "album"];
+const ISRC: &[&str] = &["isrc"];
+const DURATION_MS: &[&str] = &["track duration (ms)", "duration (ms)", "duration_ms"];
+/// Durations in seconds or as `m:ss`
+const DURATION: &[&str] = &["duration", "length"];
+const SPOTIFY: &[&str] = &["track uri", "spotify uri", "spotify_uri", "uri", "spotify id", "spotify_id"];
+
+/// Read a CSV file with a header row. Artists are separated by `;`, or by `,` if no artist field of the file contains a `;`
+pub(super) fn read(contents: &str) -> Result<Playlist> {
+    let mut reader = ::csv::ReaderBuilder::new()
+        .flexible(true)
+        .from_reader(contents.as_bytes());
+
+    let columns: HashMap<String, usize> = reader.headers()?
+        .iter()
+        .enumerate()
+        .map(|(i, h)| (h.trim().to_lowercase(), i))
+        .collect();
+    let column = |names: &[&str]| names.iter().find_map(|n| columns.get(*n).copied());
+
+    let title = column(TITLE).ok_or_else(|| Error::PlaylistFile(format!("no title column, expected one of: {}", TITLE.join(", "))))?;
+    let artists = column(ARTISTS);
+    let album = column(ALBUM);
+    let isrc = column(ISRC);
+    let duration_ms = column(DURATION_MS);
+    let duration = column(DURATION);
+    let spotify_id = column(SPOTIFY);
+
+    let records = reader.records().collect::<std::result::Result<Vec<_>, _>>()?;
+    let separator = super::artist_separator(records.iter().filter_map(|r| r.get(artists?)));
+
+    let mut tracks = Vec::new();
+    for record in records {
+        let field = |i: Option<usize>| i
+            .and_then(|i| record.get(i))
+            .map(str::trim)
+            .filter(|f| !f.is_empty());
+
+        let name = match field(Some(title)) {
+            Some(n) => n.to_string(),
+            None => continue
+        };
+
+        tracks.push(PlaylistTrack {
+            id: field(spotify_id).and_then(spotify::parse_track_id).map(str::to_string),
+            name,
+            artists: field(artists).map(|a| super::split_artists(a, separator)).unwrap_or_default(),
+            album: field(album).map(str::to_string),
+            isrc: field(isrc).map(str::to_uppercase),
+            duration_ms: field(duration_ms).and_then(|d| d.parse().ok())
+                .or_else(|| field(duration).and_then(parse_duration))
+        });
+    }
+
+    Ok(Playlist {
+        tracks,
+        name: String::new(),
+        description: None,
+        url: String::new()
+    })
+}
+
+/// Write a CSV file with the same headers as an Exportify export
+pub(super) fn write(playlist: &Playlist) -> Result<String> {
+    let mut writer = ::csv::Writer::from_writer(Vec::new());
+    writer.write_record(["Track Name", "Artist Name(s)", "Album Name", "ISRC", "Track Duration (ms)", "Track URI"])?;
+    for t in &playlist.tracks {
+        writer.write_record([
+            t.name.clone(),
+            super::join_artists(&t.artists),
+            t.album.clone().unwrap_or_default(),
+            t.isrc.clone().unwrap_or_default(),
+            t.duration_ms.map(|d| d.to_string()).unwrap_or_default(),
+            super::spotify_uri(t).unwrap_or_default()
+        ])?;
+    }
+
+    let bytes = writer.into_inner().map_err(|e| e.into_error())?;
+    Ok(String::from_utf8_lossy(&bytes).into_owned())
+}
+
+/// Parse a duration in seconds, or as `m:ss` or `h:mm:ss`, to milliseconds
+fn parse_duration(duration: &str) -> Option<u64> {
+    let mut seconds = 0.0;
+    for part in duration.split(':') {
+        seconds = seconds * 60.0 + part.parse::<f64>().ok()?;
+    }
+
+    Some((seconds * 1000.0).round() as u64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn exportify() {
+        let playlist = read("\
+Track URI,Track Name,Artist Name(s),Album Name,ISRC,Track Duration (ms)
+spotify:track:4uLU6hMCjMI75M1A2tKUQC,Never Gonna Give You Up,Rick Astley,Whenever You Need Somebody,gbarl9300135,213573
+,\"September\",\"Earth, Wind & Fire\",,,
+,Get Lucky,Daft Punk; Pharrell Williams,,,
+,,Nobody,,,
+").unwrap();
+
+        assert_eq!(playlist.tracks.len(), 3);
+        let t = &playlist.tracks[0];
+        assert_eq!(t.id.as_deref(), Some("4uLU6hMCjMI75M1A2tKUQC"));
+        assert_eq!(t.name, "Never Gonna Give You Up");
+        assert_eq!(t.artists, vec!["Rick Astley"]);
+        assert_eq!(t.album.as_deref(), Some("Whenever You Need Somebody"));
+        assert_eq!(t.isrc.as_deref(), Some("GBARL9300135"));
+        assert_eq!(t.duration_ms, Some(213573));
+
+        // The file separates artists with ';', so the comma is part of the name
+        assert_eq!(playlist.tracks[1].artists, vec!["Earth, Wind & Fire"]);
+        assert_eq!(playlist.tracks[2].artists, vec!["Daft Punk", "Pharrell Williams"]);
+    }
+
+    #[test]
+    fn comma_separated_artists_and_other_headers() {
+        let playlist = read("\
+title,artist,length
+Get Lucky,\"Daft Punk, Pharrell Williams\",4:08
+").unwrap();
+
+        let t = &playlist.tracks[0];
+        assert_eq!(t.artists, vec!["Daft Punk", "Pharrell Williams"]);
+        assert_eq!(t.duration_ms, Some(248_000));
+        assert_eq!(t.id, None);
+    }
+
+    #[test]
+    fn missing_title_column() {
+        assert!(matches!(read("artist\nSomeone\n"), Err(Error::PlaylistFile(_))));
+    }
+
+    #[test]
+    fn round_trip() {
+        let track = |name: &str, artists: &[&str]| PlaylistTrack {
+            id: None,
+            name: name.to_string(),
+            artists: artists.iter().map(|a| a.to_string()).collect(),
+            album: None,
+            isrc: None,
+            duration_ms: Some(1000)
+        };
+        let playlist = Playlist {
+            tracks: vec![track("September", &["Earth, Wind & Fire"]), track("Get Lucky", &["Daft Punk", "Pharrell Williams"])],
+            name: String::new(),
+            description: None,
+            url: String::new()
+        };
+
+        let read = read(&write(&playlist).unwrap()).unwrap();
+        assert_eq!(read.tracks[0].artists, vec!["Earth, Wind & Fire"]);
+        assert_eq!(read.tracks[1].artists, vec!["Daft Punk", "Pharrell Williams"]);
+        assert_eq!(read.tracks[1].duration_ms, Some(1000));
+
+        // Without any other artist list containing a ';', the trailing one keeps the name together
+        let playlist = Playlist {
+            tracks: vec![track("September", &["Earth, Wind & Fire"])],
+            ..playlist
+        };
+        assert_eq!(super::read(&write(&playlist).unwrap()).unwrap().tracks[0].artists, vec!["Earth, Wind & Fire"]);
+    }
+
+    #[test]
+    fn durations() {
+        assert_eq!(parse_duration("248"), Some(248_000));
+        assert_eq!(parse_duration("4:08"), Some(248_000));
+        assert_eq!(parse_duration("1:02:03"), Some(3_723_000));
+        assert_eq!(parse_duration("soon"), None);
+    }
+}
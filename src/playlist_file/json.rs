@@ -0,0 +1,64 @@
+use serde::{Serialize, Deserialize};
+use crate::Result;
+use crate::service::{Playlist, PlaylistTrack};
+
+/// A playlist as written by [write]
+#[derive(Serialize, Deserialize)]
+struct File {
+    name: String,
+    description: Option<String>,
+    url: String,
+    tracks: Vec<Track>
+}
+
+#[derive(Serialize, Deserialize)]
+struct Track {
+    title: String,
+    artists: Vec<String>,
+    album: Option<String>,
+    isrc: Option<String>,
+    duration_ms: Option<u64>,
+    spotify_uri: Option<String>
+}
+
+/// Read a playlist written by [write]
+pub(super) fn read(contents: &str) -> Result<Playlist> {
+    let file: File = serde_json::from_str(contents)?;
+    Ok(Playlist {
+        tracks: file.tracks
+            .into_iter()
+            .map(|t| PlaylistTrack {
+                id: t.spotify_uri.as_deref().and_then(crate::service::spotify::parse_track_id).map(str::to_string),
+                name: t.title,
+                artists: t.artists,
+                album: t.album,
+                isrc: t.isrc,
+                duration_ms: t.duration_ms
+            })
+            .collect(),
+        name: file.name,
+        description: file.description,
+        url: file.url
+    })
+}
+
+/// Write `playlist` with all its metadata
+pub(super) fn write(playlist: &Playlist) -> Result<String> {
+    let file = File {
+        name: playlist.name.clone(),
+        description: playlist.description.clone(),
+        url: playlist.url.clone(),
+        tracks: playlist.tracks.iter()
+            .map(|t| Track {
+                title: t.name.clone(),
+                artists: t.artists.clone(),
+                album: t.album.clone(),
+                isrc: t.isrc.clone(),
+                duration_ms: t.duration_ms,
+                spotify_uri: super::spotify_uri(t)
+            })
+            .collect()
+    };
+
+    Ok(serde_json::to_string_pretty(&file)?)
+}
@@ -0,0 +1,198 @@
+use crate::service::{spotify, Playlist, PlaylistTrack};
+
+/// Read an M3U playlist. Tracks are described by their `#EXTINF:<seconds>,<artist> - <title>` line,
+/// or by the file name of the entry if they have none. Artists are separated by `;`, or by `,` if no track of the file has a `;` in its artists
+pub(super) fn read(contents: &str) -> Playlist {
+    let mut name = String::new();
+    let mut tracks = Vec::new();
+    // The artist field of every track, split once the separator of the file is known
+    let mut artists = Vec::new();
+    let mut info: Option<(Option<u64>, &str)> = None;
+
+    for line in contents.lines().map(str::trim).filter(|l| !l.is_empty()) {
+        if let Some(rest) = line.strip_prefix("#EXTINF:") {
+            // Attributes such as `tvg-id="..."` may sit between the duration and the comma, and may contain commas themselves
+            let (duration, title) = match title_start(rest) {
+                Some(i) => (&rest[..i], &rest[i + 1..]),
+                None => (rest, "")
+            };
+            let seconds = duration.split_whitespace()
+                .next()
+                .and_then(|d| d.parse::<i64>().ok())
+                .filter(|&d| d > 0);
+            info = Some((seconds.map(|s| s as u64 * 1000), title.trim()));
+        } else if let Some(playlist) = line.strip_prefix("#PLAYLIST:") {
+            name = playlist.trim().to_string();
+        } else if !line.starts_with('#') {
+            let (duration_ms, title) = info.take().unwrap_or((None, ""));
+            let title = if title.is_empty() { file_stem(line) } else { title };
+            let (artist, name) = match title.split_once(" - ") {
+                Some((artist, name)) => (artist, name.trim().to_string()),
+                None => ("", title.trim().to_string())
+            };
+
+            artists.push(artist);
+            tracks.push(PlaylistTrack {
+                id: spotify::parse_track_id(line).map(str::to_string),
+                name,
+                artists: Vec::new(),
+                album: None,
+                isrc: None,
+                duration_ms
+            });
+        }
+    }
+
+    let separator = super::artist_separator(artists.iter().copied());
+    for (t, artist) in tracks.iter_mut().zip(artists) {
+        t.artists = super::split_artists(artist, separator);
+    }
+
+    Playlist {
+        tracks,
+        name,
+        description: None,
+        url: String::new()
+    }
+}
+
+/// Write an extended M3U playlist, with the Spotify URI as location of every track. Tracks not on Spotify are left out,
+/// as there is nothing to point to
+pub(super) fn write(playlist: &Playlist) -> String {
+    let mut out = format!("#EXTM3U\n#PLAYLIST:{}\n", single_line(&playlist.name));
+    for t in &playlist.tracks {
+        let uri = match super::spotify_uri(t) {
+            Some(uri) => uri,
+            None => continue
+        };
+
+        let seconds = t.duration_ms.map(|d| (d as f64 / 1000.0).round() as i64).unwrap_or(-1);
+        let title = if t.artists.is_empty() {
+            t.name.clone()
+        } else {
+            format!("{} - {}", super::join_artists(&t.artists), t.name)
+        };
+        out.push_str(&format!("#EXTINF:{},{}\n{}\n", seconds, single_line(&title), uri));
+    }
+
+    out
+}
+
+/// Index of the comma ending the duration and attributes of an `#EXTINF` directive, skipping commas in quoted attribute values
+fn title_start(extinf: &str) -> Option<usize> {
+    let mut quoted = false;
+    extinf.char_indices().find_map(|(i, c)| match c {
+        '"' => {
+            quoted = !quoted;
+            None
+        },
+        ',' if !quoted => Some(i),
+        _ => None
+    })
+}
+
+/// Replace line breaks, which would end an M3U directive
+fn single_line(s: &str) -> String {
+    s.replace(['\r', '\n'], " ")
+}
+
+/// The file name of an entry without directories and extension. Entries may be Windows paths or URLs
+fn file_stem(location: &str) -> &str {
+    let name = location.rsplit(['/', '\\']).next().unwrap_or(location);
+    match name.rfind('.') {
+        Some(i) if i > 0 => &name[..i],
+        _ => name
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn extended() {
+        let playlist = read("\
+#EXTM3U
+#PLAYLIST:Road Trip
+#EXTINF:213,Rick Astley - Never Gonna Give You Up
+spotify:track:4uLU6hMCjMI75M1A2tKUQC
+#EXTINF:-1,Daft Punk, Pharrell Williams - Get Lucky
+https://example.com/get-lucky.mp3
+");
+
+        assert_eq!(playlist.name, "Road Trip");
+        assert_eq!(playlist.tracks.len(), 2);
+
+        let t = &playlist.tracks[0];
+        assert_eq!(t.id.as_deref(), Some("4uLU6hMCjMI75M1A2tKUQC"));
+        assert_eq!(t.name, "Never Gonna Give You Up");
+        assert_eq!(t.artists, vec!["Rick Astley"]);
+        assert_eq!(t.duration_ms, Some(213_000));
+
+        let t = &playlist.tracks[1];
+        assert_eq!(t.id, None);
+        assert_eq!(t.artists, vec!["Daft Punk", "Pharrell Williams"]);
+        assert_eq!(t.duration_ms, None);
+    }
+
+    #[test]
+    fn semicolon_separated_artists() {
+        let playlist = read("\
+#EXTINF:215,Earth, Wind & Fire - September
+september.mp3
+#EXTINF:248,Daft Punk; Pharrell Williams - Get Lucky
+get-lucky.mp3
+");
+
+        assert_eq!(playlist.tracks[0].artists, vec!["Earth, Wind & Fire"]);
+        assert_eq!(playlist.tracks[1].artists, vec!["Daft Punk", "Pharrell Williams"]);
+    }
+
+    #[test]
+    fn extinf_attributes() {
+        let playlist = read("\
+#EXTM3U
+#EXTINF:248 tvg-id=\"a,b\" group-title=\"Pop, Funk\",Daft Punk - Get Lucky
+get-lucky.mp3
+");
+
+        let t = &playlist.tracks[0];
+        assert_eq!(t.name, "Get Lucky");
+        assert_eq!(t.artists, vec!["Daft Punk"]);
+        assert_eq!(t.duration_ms, Some(248_000));
+    }
+
+    #[test]
+    fn plain() {
+        let playlist = read("C:\\Music\\Daft Punk - Get Lucky.mp3\n/music/Untitled.flac\n");
+
+        assert_eq!(playlist.tracks[0].name, "Get Lucky");
+        assert_eq!(playlist.tracks[0].artists, vec!["Daft Punk"]);
+        assert_eq!(playlist.tracks[1].name, "Untitled");
+        assert!(playlist.tracks[1].artists.is_empty());
+    }
+
+    #[test]
+    fn round_trip() {
+        let track = |name: &str, artists: &[&str]| PlaylistTrack {
+            id: Some("4uLU6hMCjMI75M1A2tKUQC".to_string()),
+            name: name.to_string(),
+            artists: artists.iter().map(|a| a.to_string()).collect(),
+            album: None,
+            isrc: None,
+            duration_ms: Some(1000)
+        };
+        let playlist = read(&write(&Playlist {
+            tracks: vec![track("September", &["Earth, Wind & Fire"]), track("Get Lucky", &["Daft Punk", "Pharrell Williams"])],
+            name: "Mix".to_string(),
+            description: None,
+            url: String::new()
+        }));
+
+        assert_eq!(playlist.name, "Mix");
+        assert_eq!(playlist.tracks[0].artists, vec!["Earth, Wind & Fire"]);
+        assert_eq!(playlist.tracks[1].name, "Get Lucky");
+        assert_eq!(playlist.tracks[1].artists, vec!["Daft Punk", "Pharrell Williams"]);
+        assert_eq!(playlist.tracks[1].duration_ms, Some(1000));
+    }
+}
@@ -0,0 +1,124 @@
+use std::fs;
+use std::path::Path;
+use crate::{Error, Result};
+use crate::service::{Playlist, PlaylistTrack};
+
+mod csv;
+mod json;
+mod m3u;
+mod xspf;
+
+/// The playlist file formats we understand
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    /// A table with a header row, e.g. an Exportify export
+    Csv,
+    /// Our own format, holding all metadata we have of a playlist
+    Json,
+    /// An M3U or M3U8 playlist, with the metadata in `#EXTINF` lines
+    M3u,
+    /// An XML Shareable Playlist Format playlist
+    Xspf
+}
+
+impl Format {
+    /// Pick the format by the extension of `path`
+    ///
+    /// ## Errors
+    /// - If the extension is not one of a supported format
+    pub fn from_path(path: &Path) -> Result<Self> {
+        match path.extension().and_then(|e| e.to_str()).map(str::to_lowercase).as_deref() {
+            Some("csv") => Ok(Self::Csv),
+            Some("json") => Ok(Self::Json),
+            Some("m3u" | "m3u8") => Ok(Self::M3u),
+            Some("xspf") => Ok(Self::Xspf),
+            _ => Err(Error::invalid_value("playlist file format, use a .csv, .json, .m3u, .m3u8 or .xspf file", &path.display().to_string()))
+        }
+    }
+}
+
+/// Read the playlist stored at `path`, in the format its extension indicates.
+/// Playlists without a name of their own are named after the file. The link is left empty unless the file has one,
+/// as the local path is no use to anyone else and would leak the user's directory layout into published descriptions
+///
+/// ## Errors
+/// - If the extension is not one of a supported format
+/// - IO errors
+/// - If the file is not a valid playlist of its format
+pub fn read(path: &Path) -> Result<Playlist> {
+    let format = Format::from_path(path)?;
+    let contents = decode(fs::read(path)?);
+    let mut playlist = match format {
+        Format::Csv => csv::read(&contents)?,
+        Format::Json => json::read(&contents)?,
+        Format::M3u => m3u::read(&contents),
+        Format::Xspf => xspf::read(&contents)?
+    };
+
+    if playlist.name.is_empty() {
+        playlist.name = path.file_stem()
+            .map(|s| s.to_string_lossy().into_owned())
+            .unwrap_or_default();
+    }
+
+    Ok(playlist)
+}
+
+/// Write `playlist` to `path`, in the format its extension indicates. Every format but M3U keeps all metadata of the tracks
+///
+/// ## Errors
+/// - If the extension is not one of a supported format
+/// - IO errors
+pub fn write(playlist: &Playlist, path: &Path) -> Result<()> {
+    let contents = match Format::from_path(path)? {
+        Format::Csv => csv::write(playlist)?,
+        Format::Json => json::write(playlist)?,
+        Format::M3u => m3u::write(playlist),
+        Format::Xspf => xspf::write(playlist)
+    };
+
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+
+    fs::write(path, contents)?;
+    Ok(())
+}
+
+/// The Spotify URI of `track`, if it is a Spotify track
+fn spotify_uri(track: &PlaylistTrack) -> Option<String> {
+    track.id.as_ref().map(|id| format!("spotify:track:{}", id))
+}
+
+/// Decode a file as UTF-8, falling back to Latin-1, which plain `.m3u` files written by older players use
+fn decode(bytes: Vec<u8>) -> String {
+    match String::from_utf8(bytes) {
+        Ok(s) => s.trim_start_matches('\u{feff}').to_string(),
+        Err(e) => e.into_bytes().into_iter().map(char::from).collect()
+    }
+}
+
+/// The separator of the lists of artists in `fields`: `;` if any of them contains one, otherwise `,`.
+/// Deciding for all fields of a file at once keeps single artists with a comma in their name, e.g. `Earth, Wind & Fire`, intact
+/// in files separating artists with `;`
+fn artist_separator<'a>(mut fields: impl Iterator<Item = &'a str>) -> char {
+    if fields.any(|f| f.contains(';')) { ';' } else { ',' }
+}
+
+/// Join a list of artists with `; `, which [artist_separator] picks when reading the file back.
+/// A single artist with a comma in their name gets a trailing `;`, so it isn't split on the comma in files without any other `;`
+fn join_artists(artists: &[String]) -> String {
+    match artists {
+        [artist] if artist.contains(',') => format!("{};", artist),
+        _ => artists.join("; ")
+    }
+}
+
+/// Split a list of artists separated by `separator`
+fn split_artists(artists: &str, separator: char) -> Vec<String> {
+    artists.split(separator)
+        .map(str::trim)
+        .filter(|a| !a.is_empty())
+        .map(str::to_string)
+        .collect()
+}
@@ -0,0 +1,234 @@
+use quick_xml::events::Event;
+use quick_xml::Reader;
+use crate::{Error, Result};
+use crate::service::{spotify, Playlist, PlaylistTrack};
+
+/// Read an XSPF playlist. Spotify URIs and ISRCs are taken from the `identifier` elements of a track.
+/// Artists are separated by `;`, or by `,` if no `creator` of the file contains a `;`
+pub(super) fn read(contents: &str) -> Result<Playlist> {
+    let mut reader = Reader::from_str(contents);
+    reader.trim_text(true);
+
+    let mut playlist = Playlist {
+        tracks: Vec::new(),
+        name: String::new(),
+        description: None,
+        url: String::new()
+    };
+    // Element names from the root to the current element
+    let mut path: Vec<String> = Vec::new();
+    let mut track: Option<PlaylistTrack> = None;
+    // The creator of the current track, and of every track read, split once the separator of the file is known
+    let mut creator = String::new();
+    let mut creators = Vec::new();
+
+    loop {
+        match reader.read_event()? {
+            Event::Start(e) => {
+                let name = String::from_utf8_lossy(e.local_name().as_ref()).into_owned();
+                if path.is_empty() && name != "playlist" {
+                    return Err(Error::PlaylistFile(format!("expected an XSPF playlist, found <{}>", name)));
+                }
+                if name == "track" {
+                    creator.clear();
+                    track = Some(PlaylistTrack {
+                        id: None,
+                        name: String::new(),
+                        artists: Vec::new(),
+                        album: None,
+                        isrc: None,
+                        duration_ms: None
+                    });
+                }
+                path.push(name);
+            },
+            Event::End(e) => {
+                if e.local_name().as_ref() == b"track" {
+                    if let Some(t) = track.take().filter(|t| !t.name.is_empty()) {
+                        playlist.tracks.push(t);
+                        creators.push(std::mem::take(&mut creator));
+                    }
+                }
+                path.pop();
+            },
+            Event::Text(e) => set(&mut playlist, track.as_mut(), &mut creator, &path, e.unescape()?.trim()),
+            Event::CData(e) => set(&mut playlist, track.as_mut(), &mut creator, &path, String::from_utf8_lossy(&e).trim()),
+            Event::Eof => break,
+            _ => {}
+        }
+    }
+
+    let separator = super::artist_separator(creators.iter().map(String::as_str));
+    for (t, creator) in playlist.tracks.iter_mut().zip(&creators) {
+        t.artists = super::split_artists(creator, separator);
+    }
+
+    Ok(playlist)
+}
+
+/// Write an XSPF playlist. The Spotify URI and ISRC of every track are written as `identifier`s
+pub(super) fn write(playlist: &Playlist) -> String {
+    let mut out = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<playlist version=\"1\" xmlns=\"http://xspf.org/ns/0/\">\n");
+    out.push_str(&element(1, "title", &playlist.name));
+    if let Some(description) = &playlist.description {
+        out.push_str(&element(1, "annotation", description));
+    }
+    if !playlist.url.is_empty() {
+        out.push_str(&element(1, "location", &playlist.url));
+    }
+
+    out.push_str("  <trackList>\n");
+    for t in &playlist.tracks {
+        out.push_str("    <track>\n");
+        if let Some(uri) = super::spotify_uri(t) {
+            out.push_str(&element(3, "identifier", &uri));
+        }
+        if let Some(isrc) = &t.isrc {
+            out.push_str(&element(3, "identifier", &format!("isrc:{}", isrc)));
+        }
+        out.push_str(&element(3, "title", &t.name));
+        if !t.artists.is_empty() {
+            out.push_str(&element(3, "creator", &super::join_artists(&t.artists)));
+        }
+        if let Some(album) = &t.album {
+            out.push_str(&element(3, "album", album));
+        }
+        if let Some(duration) = t.duration_ms {
+            out.push_str(&element(3, "duration", &duration.to_string()));
+        }
+        out.push_str("    </track>\n");
+    }
+    out.push_str("  </trackList>\n</playlist>\n");
+
+    out
+}
+
+/// An element containing only `text`, indented by `depth` levels
+fn element(depth: usize, name: &str, text: &str) -> String {
+    format!("{}<{name}>{}</{name}>\n", "  ".repeat(depth), quick_xml::escape::escape(text), name = name)
+}
+
+/// Store the text of the element at `path`. The creator of a track is stored in `creator`
+fn set(playlist: &mut Playlist, track: Option<&mut PlaylistTrack>, creator: &mut String, path: &[String], text: &str) {
+    let element = path.iter().map(String::as_str).collect::<Vec<_>>();
+    match (element.as_slice(), track) {
+        ([.., "track", "title"], Some(t)) => t.name = text.to_string(),
+        ([.., "track", "creator"], Some(_)) => *creator = text.to_string(),
+        ([.., "track", "album"], Some(t)) => t.album = Some(text.to_string()),
+        ([.., "track", "duration"], Some(t)) => t.duration_ms = text.parse().ok(),
+        ([.., "track", "identifier" | "location"], Some(t)) => {
+            if let Some(isrc) = text.strip_prefix("isrc:") {
+                t.isrc = Some(isrc.to_uppercase());
+            } else if let Some(id) = spotify::parse_track_id(text) {
+                t.id = Some(id.to_string());
+            }
+        },
+        (["playlist", "title"], _) => playlist.name = text.to_string(),
+        (["playlist", "annotation"], _) => playlist.description = Some(text.to_string()),
+        (["playlist", "location"], _) => playlist.url = text.to_string(),
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tracks_and_identifiers() {
+        let playlist = read(r#"<?xml version="1.0" encoding="UTF-8"?>
+<playlist version="1" xmlns="http://xspf.org/ns/0/">
+  <title>Road Trip</title>
+  <annotation>Songs &amp; more</annotation>
+  <trackList>
+    <track>
+      <identifier>spotify:track:4uLU6hMCjMI75M1A2tKUQC</identifier>
+      <identifier>isrc:gbarl9300135</identifier>
+      <title>Never Gonna Give You Up</title>
+      <creator>Rick Astley</creator>
+      <album><![CDATA[Whenever You Need Somebody]]></album>
+      <duration>213573</duration>
+    </track>
+    <track>
+      <location>https://open.spotify.com/intl-de/track/4uLU6hMCjMI75M1A2tKUQC?si=x</location>
+      <title>Get Lucky</title>
+      <creator>Daft Punk; Pharrell Williams</creator>
+    </track>
+    <track>
+      <title>September</title>
+      <creator>Earth, Wind &amp; Fire</creator>
+    </track>
+    <track>
+      <creator>No title</creator>
+    </track>
+  </trackList>
+</playlist>
+"#).unwrap();
+
+        assert_eq!(playlist.name, "Road Trip");
+        assert_eq!(playlist.description.as_deref(), Some("Songs & more"));
+        assert_eq!(playlist.tracks.len(), 3);
+
+        let t = &playlist.tracks[0];
+        assert_eq!(t.id.as_deref(), Some("4uLU6hMCjMI75M1A2tKUQC"));
+        assert_eq!(t.isrc.as_deref(), Some("GBARL9300135"));
+        assert_eq!(t.name, "Never Gonna Give You Up");
+        assert_eq!(t.artists, vec!["Rick Astley"]);
+        assert_eq!(t.album.as_deref(), Some("Whenever You Need Somebody"));
+        assert_eq!(t.duration_ms, Some(213573));
+
+        let t = &playlist.tracks[1];
+        assert_eq!(t.id.as_deref(), Some("4uLU6hMCjMI75M1A2tKUQC"));
+        assert_eq!(t.artists, vec!["Daft Punk", "Pharrell Williams"]);
+
+        // The file separates artists with ';', so the comma is part of the name
+        assert_eq!(playlist.tracks[2].artists, vec!["Earth, Wind & Fire"]);
+    }
+
+    #[test]
+    fn not_xspf() {
+        assert!(matches!(read("<html><body/></html>"), Err(Error::PlaylistFile(_))));
+    }
+
+    #[test]
+    fn round_trip() {
+        let playlist = read(&write(&Playlist {
+            tracks: vec![PlaylistTrack {
+                id: Some("4uLU6hMCjMI75M1A2tKUQC".to_string()),
+                name: "Rock & Roll <Live>".to_string(),
+                artists: vec!["A".to_string(), "B".to_string()],
+                album: None,
+                isrc: Some("GBARL9300135".to_string()),
+                duration_ms: Some(1000)
+            }],
+            name: "Mix".to_string(),
+            description: None,
+            url: String::new()
+        })).unwrap();
+
+        let t = &playlist.tracks[0];
+        assert_eq!(t.name, "Rock & Roll <Live>");
+        assert_eq!(t.artists, vec!["A", "B"]);
+        assert_eq!(t.isrc.as_deref(), Some("GBARL9300135"));
+        assert_eq!(t.id.as_deref(), Some("4uLU6hMCjMI75M1A2tKUQC"));
+    }
+
+    #[test]
+    fn round_trip_comma_in_artist() {
+        let playlist = read(&write(&Playlist {
+            tracks: vec![PlaylistTrack {
+                id: None,
+                name: "September".to_string(),
+                artists: vec!["Earth, Wind & Fire".to_string()],
+                album: None,
+                isrc: None,
+                duration_ms: None
+            }],
+            name: "Mix".to_string(),
+            description: None,
+            url: String::new()
+        })).unwrap();
+
+        assert_eq!(playlist.tracks[0].artists, vec!["Earth, Wind & Fire"]);
+    }
+}
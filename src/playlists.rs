@@ -0,0 +1,58 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use crate::{Error, Result};
+
+/// Remembers which playlist each source playlist was converted to, so later runs can sync into it.
+/// Spotify and YouTube playlist IDs never collide, so conversions in both directions share the map
+pub struct PlaylistMap {
+    path: PathBuf,
+    /// Source playlist ID to target playlist ID
+    entries: BTreeMap<String, String>
+}
+
+impl PlaylistMap {
+    /// The default location of the playlist map
+    pub fn default_path() -> Result<PathBuf> {
+        let dir = dirs::data_dir().ok_or(Error::NoDirectory("data"))?;
+        Ok(dir.join(env!("CARGO_PKG_NAME")).join("playlists.json"))
+    }
+
+    /// Load the playlist map stored at `path`. A missing file is treated as an empty map
+    ///
+    /// ## Errors
+    /// - IO errors
+    /// - If the file is not a valid playlist map
+    pub fn load(path: &Path) -> Result<Self> {
+        let entries = if path.exists() {
+            serde_json::from_str(&fs::read_to_string(path)?)?
+        } else {
+            BTreeMap::new()
+        };
+
+        Ok(Self {
+            path: path.to_path_buf(),
+            entries
+        })
+    }
+
+    /// The playlist the source playlist `source_id` was last converted to
+    pub fn get(&self, source_id: &str) -> Option<&str> {
+        self.entries.get(source_id).map(String::as_str)
+    }
+
+    /// Record that the source playlist `source_id` was converted to `target_id` and save the map
+    ///
+    /// ## Errors
+    /// - IO errors
+    pub fn set(&mut self, source_id: &str, target_id: &str) -> Result<()> {
+        self.entries.insert(source_id.to_string(), target_id.to_string());
+
+        if let Some(parent) = self.path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+
+        fs::write(&self.path, serde_json::to_string_pretty(&self.entries)?)?;
+        Ok(())
+    }
+}
@@ -0,0 +1,185 @@
+use std::fs;
+use std::path::Path;
+use crate::{Error, Result};
+use serde::Serialize;
+use crate::job::{Job, Status};
+use crate::matcher::Strategy;
+
+/// The outcome of a single source track
+#[derive(Serialize)]
+struct Entry<'a> {
+    /// Position in the source playlist, starting at 1
+    position: usize,
+    title: &'a str,
+    artists: String,
+    album: Option<&'a str>,
+    spotify_id: Option<&'a str>,
+    isrc: Option<&'a str>,
+    status: Status,
+    /// ID of the item of the target service the track resolved to
+    match_id: Option<&'a str>,
+    match_url: Option<String>,
+    match_title: Option<&'a str>,
+    score: Option<f64>,
+    strategy: Option<Strategy>,
+    /// Position of the track this one is a duplicate of
+    duplicate_of: Option<usize>,
+    error: Option<&'a str>
+}
+
+#[derive(Serialize)]
+struct Report<'a> {
+    name: &'a str,
+    source_url: &'a str,
+    target_service: &'static str,
+    target_playlist_id: Option<&'a str>,
+    target_playlist_url: Option<String>,
+    tracks: Vec<Entry<'a>>
+}
+
+/// Write a report on every track of `job` to `path`. The format is picked by the extension: `.json`, `.csv` or `.html`
+///
+/// ## Errors
+/// - If the extension is not one of the above
+/// - IO errors
+pub fn write(job: &Job, path: &Path) -> Result<()> {
+    let report = build(job);
+    let contents = match path.extension().and_then(|e| e.to_str()).map(str::to_lowercase).as_deref() {
+        Some("json") => serde_json::to_string_pretty(&report)?,
+        Some("csv") => to_csv(&report)?,
+        Some("html" | "htm") => to_html(&report),
+        _ => return Err(Error::invalid_value("report format, use a .json, .csv or .html file", &path.display().to_string()))
+    };
+
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+
+    fs::write(path, contents)?;
+    Ok(())
+}
+
+fn build(job: &Job) -> Report<'_> {
+    let tracks = job.tracks.iter()
+        .enumerate()
+        .map(|(i, t)| {
+            let candidate = t.found.as_ref().map(|m| &m.candidate);
+            Entry {
+                position: i + 1,
+                title: &t.track.name,
+                artists: t.track.artists.join(", "),
+                album: t.track.album.as_deref(),
+                spotify_id: t.track.id.as_deref(),
+                isrc: t.track.isrc.as_deref(),
+                status: t.status,
+                match_id: candidate.map(|c| c.id.as_str()),
+                match_url: candidate.map(|c| job.direction.target_item_url(&c.id)),
+                match_title: candidate.map(|c| c.title.as_str()).filter(|t| !t.is_empty()),
+                score: t.found.as_ref().map(|m| m.score),
+                strategy: t.found.as_ref().map(|m| m.strategy),
+                duplicate_of: t.duplicate_of.map(|o| o + 1),
+                error: t.error.as_deref()
+            }
+        })
+        .collect();
+
+    Report {
+        name: &job.source.name,
+        source_url: &job.source.url,
+        target_service: job.direction.target_name(),
+        target_playlist_id: job.target_playlist_id.as_deref(),
+        target_playlist_url: job.target_playlist_id.as_deref().map(|id| job.direction.target_playlist_url(id)),
+        tracks
+    }
+}
+
+fn to_csv(report: &Report) -> Result<String> {
+    let mut writer = csv::Writer::from_writer(Vec::new());
+    for entry in &report.tracks {
+        writer.serialize(entry)?;
+    }
+
+    let bytes = writer.into_inner().map_err(|e| e.into_error())?;
+    Ok(String::from_utf8_lossy(&bytes).into_owned())
+}
+
+/// A self-contained page with a table of all tracks, to share with people who don't want to read JSON
+fn to_html(report: &Report) -> String {
+    let mut rows = String::new();
+    for e in &report.tracks {
+        let found = match (&e.match_url, e.match_id) {
+            (Some(url), Some(id)) => format!("<a href=\"{}\">{}</a>", escape(url), escape(e.match_title.unwrap_or(id))),
+            _ => String::new()
+        };
+        let note = match (e.status, e.duplicate_of, e.error) {
+            (Status::Duplicate, Some(original), _) => format!("Same as #{}", original),
+            (_, _, Some(error)) => escape(error),
+            _ => String::new()
+        };
+
+        rows.push_str(&format!(
+            "<tr class=\"{status}\"><td>{}</td><td>{}</td><td>{}</td><td>{}</td><td>{}</td><td>{}</td><td>{status}</td><td>{}</td></tr>\n",
+            e.position,
+            escape(e.title),
+            escape(&e.artists),
+            found,
+            e.score.map(|s| format!("{:.2}", s)).unwrap_or_default(),
+            e.strategy.map(|s| s.to_string()).unwrap_or_default(),
+            note,
+            status = status_label(e.status)
+        ));
+    }
+
+    // Playlists read from a file have no link
+    let source = match report.source_url {
+        "" => String::new(),
+        url => format!("<p>Converted from <a href=\"{0}\">{0}</a></p>", escape(url))
+    };
+    let playlist = match &report.target_playlist_url {
+        Some(url) => format!("<p>{} playlist: <a href=\"{1}\">{1}</a></p>", report.target_service, escape(url)),
+        None => String::new()
+    };
+
+    format!(r#"<!DOCTYPE html>
+<html>
+<head>
+<meta charset="utf-8">
+<title>{name}</title>
+<style>
+body {{ font-family: sans-serif; margin: 2em; }}
+table {{ border-collapse: collapse; }}
+th, td {{ padding: 0.3em 0.8em; border-bottom: 1px solid #ddd; text-align: left; }}
+tr.not-found td, tr.failed td {{ background: #fdecea; }}
+tr.skipped td, tr.duplicate td {{ color: #888; }}
+</style>
+</head>
+<body>
+<h1>{name}</h1>
+{source}
+{playlist}
+<table>
+<tr><th>#</th><th>Title</th><th>Artists</th><th>{target}</th><th>Score</th><th>Strategy</th><th>Status</th><th>Note</th></tr>
+{rows}</table>
+</body>
+</html>
+"#, name = escape(report.name), source = source, target = report.target_service, playlist = playlist, rows = rows)
+}
+
+fn status_label(status: Status) -> &'static str {
+    match status {
+        Status::Pending => "pending",
+        Status::Matched => "matched",
+        Status::Inserted => "inserted",
+        Status::Skipped => "skipped",
+        Status::NotFound => "not-found",
+        Status::Duplicate => "duplicate",
+        Status::Failed => "failed"
+    }
+}
+
+fn escape(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
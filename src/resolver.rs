@@ -0,0 +1,80 @@
+use crate::Result;
+use log::debug;
+use crate::service::PlaylistTrack;
+use crate::cache::MatchCache;
+use crate::matcher::{self, Catalog, Match, MatchOptions, Outcome};
+use crate::overrides::{Override, Overrides};
+use crate::review::{self, Decision};
+
+/// The outcome of resolving a track
+pub enum Resolution {
+    Matched(Match),
+    /// The user's overrides say to leave the track out
+    Skipped,
+    NotFound
+}
+
+/// Resolves tracks to items of the target service, consulting the user's overrides first, then the match cache, and finally searching
+pub struct Resolver {
+    pub options: MatchOptions,
+    pub overrides: Overrides,
+    pub cache: Option<MatchCache>,
+    /// Number of candidates to show when asking the user to review a track without a confident match.
+    /// `None` disables interactive review
+    pub review: Option<usize>
+}
+
+impl Resolver {
+    /// Resolve `track` to an item of `catalog`
+    ///
+    /// ## Errors
+    /// - When searching fails
+    /// - When the match cache or overrides file can't be written to
+    pub fn resolve(&mut self, track: &PlaylistTrack, catalog: &dyn Catalog) -> Result<Resolution> {
+        match self.overrides.get(track) {
+            Some(Override::Skip) => {
+                debug!("Skipping '{} - {}' as configured in overrides", track.name, track.artist());
+                return Ok(Resolution::Skipped);
+            },
+            Some(Override::Video(id)) => {
+                debug!("Using override {} for '{} - {}'", id, track.name, track.artist());
+                return Ok(Resolution::Matched(Match::manual(id)));
+            },
+            None => {}
+        }
+
+        if let Some(m) = self.cache.as_ref().and_then(|c| c.get(track)) {
+            debug!("Using cached match for '{} - {}'", track.name, track.artist());
+            return Ok(Resolution::Matched(m.clone()));
+        }
+
+        let candidates = match matcher::find_match(track, &self.options, catalog)? {
+            Outcome::Matched(m) => {
+                if let Some(cache) = self.cache.as_mut() {
+                    cache.insert(track, &m)?;
+                }
+
+                return Ok(Resolution::Matched(m));
+            },
+            Outcome::Unmatched(candidates) => candidates
+        };
+
+        let count = match self.review {
+            Some(count) => count,
+            None => return Ok(Resolution::NotFound)
+        };
+
+        // Decisions are remembered as overrides, so the user is never asked about the same track twice
+        match review::review(track, candidates, count, catalog)? {
+            Decision::Matched(m) => {
+                self.overrides.set(track, Override::Video(m.candidate.id.clone()))?;
+                Ok(Resolution::Matched(m))
+            },
+            Decision::Skip => {
+                self.overrides.set(track, Override::Skip)?;
+                Ok(Resolution::Skipped)
+            },
+            Decision::Undecided => Ok(Resolution::NotFound)
+        }
+    }
+}
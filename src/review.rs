@@ -0,0 +1,82 @@
+use std::io::{self, BufRead, Write};
+use crate::Result;
+use crate::service::PlaylistTrack;
+use crate::matcher::{self, Catalog, Match, Strategy};
+
+/// The user's decision about a track
+pub enum Decision {
+    Matched(Match),
+    Skip,
+    /// The user did not decide, leave the track unmatched without remembering anything
+    Undecided
+}
+
+/// Show `track` next to the best `count` of `candidates` and ask the user what to do with it.
+/// The user can pick a candidate, skip the track, search `catalog` with their own query or enter an ID
+///
+/// ## Errors
+/// - IO errors
+/// - When a search request fails
+pub fn review(track: &PlaylistTrack, mut candidates: Vec<Match>, count: usize, catalog: &dyn Catalog) -> Result<Decision> {
+    let stdin = io::stdin();
+    let mut lines = stdin.lock().lines();
+
+    loop {
+        println!();
+        println!("No confident match for:");
+        println!("    {} - {} [{}] ({})", track.name, track.artists.join(", "), track.album.as_deref().unwrap_or("-"), format_duration(track.duration_ms));
+
+        if candidates.is_empty() {
+            println!("No candidates found");
+        }
+
+        for (i, m) in candidates.iter().take(count).enumerate() {
+            let c = &m.candidate;
+            println!("{:>4}. {} - {} [{}] ({}), score {:.2}", i + 1, c.title, c.artists.join(", "), c.album.as_deref().unwrap_or("-"), format_duration(c.duration_ms), m.score);
+        }
+
+        let shown = candidates.len().min(count);
+        if shown > 0 {
+            print!("Pick [1-{}], ", shown);
+        }
+        print!("(s)kip, (q)uery <terms>, (i)d <{} id or url>, or press enter to leave unmatched: ", catalog.item_name());
+        io::stdout().flush()?;
+
+        let line = match lines.next() {
+            Some(line) => line?,
+            None => return Ok(Decision::Undecided)
+        };
+
+        let line = line.trim();
+        let (command, argument) = line.split_once(' ')
+            .map(|(c, a)| (c, a.trim()))
+            .unwrap_or((line, ""));
+
+        match command {
+            "" => return Ok(Decision::Undecided),
+            "s" | "skip" => return Ok(Decision::Skip),
+            "q" | "query" if !argument.is_empty() => {
+                candidates = matcher::rank(track, catalog.search(argument)?, Strategy::Manual);
+            },
+            "i" | "id" | "v" | "video" => match catalog.parse_id(argument) {
+                Some(id) => return Ok(Decision::Matched(Match::manual(&id))),
+                None => println!("'{}' is not a {} ID or URL", argument, catalog.item_name())
+            },
+            n => match n.parse::<usize>() {
+                Ok(n) if n >= 1 && n <= shown => {
+                    let mut m = candidates.swap_remove(n - 1);
+                    m.strategy = Strategy::Manual;
+                    return Ok(Decision::Matched(m));
+                },
+                _ => println!("Invalid choice '{}'", line)
+            }
+        }
+    }
+}
+
+fn format_duration(duration_ms: Option<u64>) -> String {
+    match duration_ms {
+        Some(ms) => format!("{}:{:02}", ms / 60_000, ms / 1000 % 60),
+        None => "?:??".to_string()
+    }
+}
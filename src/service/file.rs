@@ -0,0 +1,31 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use sha2::Digest;
+use crate::Result;
+use crate::playlist_file;
+use crate::service::{Playlist, PlaylistSource};
+
+/// Reads a playlist file, see [playlist_file] for the formats
+pub struct FileSource {
+    pub path: PathBuf
+}
+
+impl PlaylistSource for FileSource {
+    /// Files are identified by their name without extension and a hash of their canonical path,
+    /// so files of the same name in different directories don't share a job or target playlist
+    fn parse_playlist_id(&self, url: &str) -> Option<String> {
+        let path = fs::canonicalize(url).ok()?;
+        let stem = Path::new(url).file_stem()?.to_string_lossy();
+        let hash: String = sha2::Sha256::digest(path.to_string_lossy().as_bytes())
+            .iter()
+            .take(8)
+            .map(|b| format!("{:02x}", b))
+            .collect();
+
+        Some(format!("{}-{}", stem, hash))
+    }
+
+    fn fetch_playlist(&self, _id: &str) -> Result<Playlist> {
+        playlist_file::read(&self.path)
+    }
+}
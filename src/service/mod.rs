@@ -0,0 +1,161 @@
+use crate::Result;
+use log::{debug, warn};
+use serde::{Serialize, Deserialize};
+use crate::job::{Job, Status};
+use crate::matcher::Catalog;
+
+/// Playlist files as source
+pub mod file;
+/// Spotify as source and target
+pub mod spotify;
+/// YouTube as source, and YouTube or YouTube Music as target
+pub mod youtube;
+
+/// A playlist read from a source service
+pub struct Playlist {
+    pub tracks: Vec<PlaylistTrack>,
+    pub name: String,
+    /// Plain text description, `None` if the playlist has none
+    pub description: Option<String>,
+    /// Link to the playlist in the source service. Empty for playlist files which don't name one
+    pub url: String
+}
+
+/// A track of a source playlist
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PlaylistTrack {
+    /// The Spotify track ID. `None` for local files and tracks from other services
+    pub id: Option<String>,
+    pub name: String,
+    pub artists: Vec<String>,
+    pub album: Option<String>,
+    pub isrc: Option<String>,
+    pub duration_ms: Option<u64>
+}
+
+impl PlaylistTrack {
+    /// The primary artist of the track, or an empty string if the source did not list any
+    pub fn artist(&self) -> &str {
+        self.artists.first().map(String::as_str).unwrap_or_default()
+    }
+}
+
+/// Who can see a playlist
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Privacy {
+    Public,
+    Unlisted,
+    Private
+}
+
+impl std::str::FromStr for Privacy {
+    type Err = crate::Error;
+
+    fn from_str(s: &str) -> crate::Result<Self> {
+        match s {
+            "public" => Ok(Self::Public),
+            "unlisted" => Ok(Self::Unlisted),
+            "private" => Ok(Self::Private),
+            _ => Err(crate::Error::invalid_value("privacy status", s))
+        }
+    }
+}
+
+/// The metadata of a playlist to create
+pub struct PlaylistDetails {
+    pub title: String,
+    pub description: String,
+    /// `None` leaves the privacy status at the target service's default
+    pub privacy: Option<Privacy>,
+    /// Only YouTube playlists have tags
+    pub tags: Vec<String>,
+    /// BCP-47 language code, e.g. `en` or `ja`
+    pub default_language: Option<String>
+}
+
+/// A service playlists can be converted from
+pub trait PlaylistSource {
+    /// Get the ID of the playlist `url` links to, or `None` if it's not a playlist URL of this service.
+    /// The ID names the playlist's job file and identifies it in the playlist map
+    fn parse_playlist_id(&self, url: &str) -> Option<String>;
+
+    /// Fetch the metadata and tracks of the playlist `id`
+    ///
+    /// ## Errors
+    /// - When the playlist doesn't exist or can't be read
+    fn fetch_playlist(&self, id: &str) -> Result<Playlist>;
+}
+
+/// A service playlists can be converted to
+pub trait PlaylistSink {
+    /// Name of the service, as shown to the user
+    fn name(&self) -> &'static str;
+
+    /// The catalog tracks are resolved against before they are added
+    fn catalog(&self) -> &dyn Catalog;
+
+    /// Called before anything is resolved or added, e.g. to warn about limits the job will run into
+    fn prepare(&self, _job: &Job) {}
+
+    /// Create a playlist, returning its ID
+    ///
+    /// ## Errors
+    /// - When the service refuses the playlist
+    fn create_playlist(&self, details: &PlaylistDetails) -> Result<String>;
+
+    /// Bring the playlist `playlist_id` in line with the matched tracks of `job`, marking every track as inserted or failed
+    /// and saving the job as it goes. `created` is set if the playlist was just created, and so is known to be empty.
+    /// Returns the number of changes which failed
+    ///
+    /// ## Errors
+    /// - When the playlist can't be read
+    /// - When the job can't be saved
+    fn add_items(&self, job: &mut Job, playlist_id: &str, created: bool) -> Result<usize>;
+}
+
+/// Append every matched track of `job` with `add`, `batch_size` at a time, saving progress after every batch.
+/// For sinks which can only append. Returns the number of batches which failed
+///
+/// ## Errors
+/// - When the job can't be saved
+pub fn append_in_batches<F>(job: &mut Job, batch_size: usize, add: F) -> Result<usize>
+where
+    F: Fn(&[String]) -> Result<()>
+{
+    let pending: Vec<usize> = job.tracks.iter()
+        .enumerate()
+        .filter(|(_, t)| t.status == Status::Matched)
+        .map(|(i, _)| i)
+        .collect();
+
+    let mut failures = 0;
+    for batch in pending.chunks(batch_size.max(1)) {
+        let ids: Vec<String> = batch.iter()
+            .filter_map(|&i| job.tracks[i].found.as_ref())
+            .map(|m| m.candidate.id.clone())
+            .collect();
+
+        debug!("Adding {} tracks to playlist", ids.len());
+        let result = add(&ids);
+        if let Err(e) = &result {
+            warn!("Failed to add {} tracks to the playlist: {}", ids.len(), e);
+            failures += 1;
+        }
+
+        for &i in batch {
+            let t = &mut job.tracks[i];
+            match &result {
+                Ok(_) => t.status = Status::Inserted,
+                Err(e) => {
+                    t.status = Status::Failed;
+                    t.error = Some(e.to_string());
+                }
+            }
+        }
+
+        job.save()?;
+    }
+
+    Ok(failures)
+}
@@ -0,0 +1,186 @@
+use std::collections::{HashMap, HashSet};
+use crate::Result;
+use log::{debug, info, warn};
+use regex::Regex;
+use crate::api::spotify;
+use crate::env::Env;
+use crate::job::{Job, Status};
+use crate::dedup::Dedup;
+use crate::matcher::{Candidate, Catalog, Strategy};
+use crate::service::{self, Playlist, PlaylistDetails, PlaylistSink, PlaylistSource, PlaylistTrack};
+
+lazy_static! {
+    /// Matches a Spotify playlist URL or URI
+    static ref PLAYLIST_ID: Regex = Regex::new(r"^(?:spotify:playlist:|https?://open\.spotify\.com/(?:intl-[a-z]+/)?playlist/)([A-Za-z0-9]+)").expect("Invalid PLAYLIST_ID regex");
+    /// Matches a Spotify track ID, either bare or as part of a Spotify URI or URL
+    static ref TRACK_ID: Regex = Regex::new(r"^(?:spotify:track:|https?://open\.spotify\.com/(?:intl-[a-z]+/)?track/)?([A-Za-z0-9]{22})(?:[?#].*)?$").expect("Invalid TRACK_ID regex");
+}
+
+/// Get the track ID from a Spotify track ID, URI or URL
+pub fn parse_track_id(input: &str) -> Option<&str> {
+    TRACK_ID.captures(input.trim()).and_then(|c| c.get(1)).map(|m| m.as_str())
+}
+
+/// Get the playlist ID from a Spotify playlist URL or URI
+pub fn parse_playlist_id(url: &str) -> Option<&str> {
+    PLAYLIST_ID.captures(url.trim()).and_then(|c| c.get(1)).map(|m| m.as_str())
+}
+
+/// Reads public Spotify playlists with an application token
+pub struct SpotifySource<'a> {
+    pub env: &'a Env
+}
+
+impl PlaylistSource for SpotifySource<'_> {
+    fn parse_playlist_id(&self, url: &str) -> Option<String> {
+        parse_playlist_id(url).map(str::to_string)
+    }
+
+    fn fetch_playlist(&self, id: &str) -> Result<Playlist> {
+        Ok(spotify::get_playlist(self.env, id)?)
+    }
+}
+
+/// The Spotify catalog, searched with any access token
+pub struct SpotifyCatalog {
+    pub auth: String
+}
+
+impl Catalog for SpotifyCatalog {
+    fn item_name(&self) -> &'static str {
+        "track"
+    }
+
+    fn query(&self, strategy: Strategy, track: &PlaylistTrack) -> Option<String> {
+        let query = strategy.query(track)?;
+        match strategy {
+            Strategy::Isrc => Some(format!("isrc:{}", query)),
+            _ => Some(query)
+        }
+    }
+
+    fn search(&self, query: &str) -> Result<Vec<Candidate>> {
+        Ok(spotify::search(query, &self.auth)?)
+    }
+
+    fn parse_id(&self, input: &str) -> Option<String> {
+        parse_track_id(input).map(str::to_string)
+    }
+}
+
+/// Writes to the playlists of a Spotify user, in batches of the most tracks Spotify accepts per request
+pub struct SpotifySink {
+    /// User access token, which is also used for searching
+    catalog: SpotifyCatalog
+}
+
+impl SpotifySink {
+    /// Write with the user access token `auth`
+    pub fn new(auth: String) -> Self {
+        Self {
+            catalog: SpotifyCatalog {
+                auth
+            }
+        }
+    }
+}
+
+impl PlaylistSink for SpotifySink {
+    fn name(&self) -> &'static str {
+        "Spotify"
+    }
+
+    fn catalog(&self) -> &dyn Catalog {
+        &self.catalog
+    }
+
+    fn create_playlist(&self, details: &PlaylistDetails) -> Result<String> {
+        Ok(spotify::create_playlist(details, &self.catalog.auth)?)
+    }
+
+    /// Appends the matched tracks which aren't in the playlist yet. With [Dedup::Existing], repeated occurrences of matched tracks already in the playlist are removed first
+    fn add_items(&self, job: &mut Job, playlist_id: &str, created: bool) -> Result<usize> {
+        if job.remove_missing {
+            warn!("Removing tracks is not supported on Spotify yet, only missing tracks will be added");
+        }
+
+        let auth = self.catalog.auth.as_str();
+        let mut failures = 0;
+        if !created {
+            debug!("Listing tracks in Spotify playlist {}", playlist_id);
+            let mut existing = spotify::list_playlist_tracks(playlist_id, auth)?;
+            if job.dedup == Dedup::Existing {
+                let matched: HashSet<&str> = job.tracks.iter()
+                    .filter(|t| matches!(t.status, Status::Matched | Status::Inserted))
+                    .filter_map(|t| t.found.as_ref())
+                    .map(|m| m.candidate.id.as_str())
+                    .collect();
+                failures += remove_duplicates(&mut existing, &matched, playlist_id, auth);
+            }
+
+            // Every occurrence of a track in the playlist accounts for one matched track, so tracks repeated on purpose are still added
+            let mut present: HashMap<&str, usize> = HashMap::new();
+            for id in existing.iter().flatten() {
+                *present.entry(id).or_default() += 1;
+            }
+
+            for t in job.tracks.iter_mut().filter(|t| matches!(t.status, Status::Matched | Status::Inserted)) {
+                let count = match t.found.as_ref().and_then(|m| present.get_mut(m.candidate.id.as_str())) {
+                    Some(count) if *count > 0 => count,
+                    _ => continue
+                };
+
+                *count -= 1;
+                t.status = Status::Inserted;
+            }
+            job.save()?;
+        }
+
+        Ok(failures + service::append_in_batches(job, spotify::MAX_TRACKS_PER_REQUEST, |ids| Ok(spotify::add_tracks(playlist_id, ids, None, auth)?))?)
+    }
+}
+
+/// Remove all but the first occurrence of every track of `matched` in the playlist `playlist_id`, whose tracks are `existing`, keeping `existing` up to date.
+/// Spotify can only remove every occurrence of a track, so the track is added back where it first occurred. Only tracks of the job are touched,
+/// as a track which fails to be added back is then added with the rest of the job. Returns the number of tracks which failed
+fn remove_duplicates(existing: &mut Vec<Option<String>>, matched: &HashSet<&str>, playlist_id: &str, auth: &str) -> usize {
+    let mut counts: HashMap<&str, usize> = HashMap::new();
+    for id in existing.iter().flatten() {
+        *counts.entry(id).or_default() += 1;
+    }
+
+    let mut repeated: Vec<String> = Vec::new();
+    for id in existing.iter().flatten() {
+        if counts[id.as_str()] > 1 && matched.contains(id.as_str()) && !repeated.contains(id) {
+            repeated.push(id.clone());
+        }
+    }
+
+    let mut failures = 0;
+    for id in repeated {
+        let first = match existing.iter().position(|t| t.as_deref() == Some(id.as_str())) {
+            Some(p) => p,
+            None => continue
+        };
+
+        if let Err(e) = spotify::remove_tracks(playlist_id, std::slice::from_ref(&id), auth) {
+            warn!("Failed to remove the repeated track {} from the playlist: {}", id, e);
+            failures += 1;
+            continue;
+        }
+        existing.retain(|t| t.as_deref() != Some(id.as_str()));
+
+        match spotify::add_tracks(playlist_id, std::slice::from_ref(&id), Some(first), auth) {
+            Ok(_) => {
+                info!("Removed repeated occurrences of track {} from the playlist", id);
+                existing.insert(first, Some(id));
+            },
+            Err(e) => {
+                warn!("Failed to add the repeated track {} back to the playlist, it will be added at the end: {}", id, e);
+                failures += 1;
+            }
+        }
+    }
+
+    failures
+}
@@ -0,0 +1,254 @@
+use crate::Result;
+use std::collections::HashSet;
+use log::{debug, error, warn};
+use regex::Regex;
+use crate::api::youtube::{self, quota};
+use crate::api::ytmusic;
+use crate::job::{Job, Status};
+use crate::matcher::{Candidate, Catalog};
+use crate::service::{self, Playlist, PlaylistDetails, PlaylistSink, PlaylistSource};
+use crate::sync::{self, Operation};
+use crate::dedup::{self, Dedup};
+
+lazy_static! {
+    /// Matches the playlist ID in a YouTube (Music) playlist URL
+    static ref PLAYLIST_ID: Regex = Regex::new(r"(?:youtube\.com/|youtu\.be/).*[?&]list=([A-Za-z0-9_-]+)").expect("Invalid PLAYLIST_ID regex");
+    /// Matches a YouTube video ID, either bare or as part of a YouTube (Music) URL
+    static ref VIDEO_ID: Regex = Regex::new(r"^(?:.*(?:[?&]v=|youtu\.be/))?([A-Za-z0-9_-]{11})(?:[?&#].*)?$").expect("Invalid VIDEO_ID regex");
+}
+
+/// Get the playlist ID from a YouTube (Music) playlist URL
+pub fn parse_playlist_id(url: &str) -> Option<&str> {
+    PLAYLIST_ID.captures(url.trim()).and_then(|c| c.get(1)).map(|m| m.as_str())
+}
+
+/// Reads YouTube playlists through the Data API, with a Google access token
+pub struct YouTubeSource<'a> {
+    pub auth: &'a str
+}
+
+impl PlaylistSource for YouTubeSource<'_> {
+    fn parse_playlist_id(&self, url: &str) -> Option<String> {
+        parse_playlist_id(url).map(str::to_string)
+    }
+
+    fn fetch_playlist(&self, id: &str) -> Result<Playlist> {
+        Ok(youtube::get_playlist(id, self.auth)?)
+    }
+}
+
+/// The YouTube Music catalog, searched without logging in
+pub struct YouTubeMusic;
+
+impl Catalog for YouTubeMusic {
+    fn item_name(&self) -> &'static str {
+        "video"
+    }
+
+    fn search(&self, query: &str) -> Result<Vec<Candidate>> {
+        youtube::search(query)
+    }
+
+    fn parse_id(&self, input: &str) -> Option<String> {
+        VIDEO_ID.captures(input.trim()).map(|c| c[1].to_string())
+    }
+}
+
+/// Writes to YouTube playlists through the Data API, with a Google access token. Subject to quota
+pub struct DataApiSink {
+    pub auth: String
+}
+
+impl PlaylistSink for DataApiSink {
+    fn name(&self) -> &'static str {
+        "YouTube"
+    }
+
+    fn catalog(&self) -> &dyn Catalog {
+        &YouTubeMusic
+    }
+
+    fn prepare(&self, job: &Job) {
+        // Every track not in the playlist yet costs one insert, plus one for creating the playlist
+        let remaining = job.tracks.iter().filter(|t| matches!(t.status, Status::Pending | Status::Matched)).count() as u64;
+        warn_quota((remaining + 1) * quota::WRITE_COST);
+    }
+
+    fn create_playlist(&self, details: &PlaylistDetails) -> Result<String> {
+        Ok(youtube::create_playlist(details, &self.auth)?)
+    }
+
+    /// Inserts, moves and removes items so the playlist ends up in the same order as the job
+    fn add_items(&self, job: &mut Job, playlist_id: &str, created: bool) -> Result<usize> {
+        let auth = self.auth.as_str();
+        let existing = if created {
+            Vec::new()
+        } else {
+            debug!("Listing items in YouTube playlist {}", playlist_id);
+            youtube::list_playlist_items(playlist_id, auth)?
+        };
+
+        // Indices into job.tracks of the tracks which should be in the playlist, in order
+        let desired: Vec<usize> = job.tracks.iter()
+            .enumerate()
+            .filter(|(_, t)| matches!(t.status, Status::Matched | Status::Inserted))
+            .map(|(i, _)| i)
+            .collect();
+        let desired_videos: Vec<String> = desired.iter()
+            .filter_map(|&i| job.tracks[i].found.as_ref())
+            .map(|m| m.candidate.id.clone())
+            .collect();
+
+        // A track which failed to resolve, say because of a network error, may still be in the playlist from an earlier run,
+        // and a failed search must never remove it. Tracks which weren't found are treated like tracks removed from the source
+        let unresolved = job.tracks.iter().filter(|t| t.status == Status::Failed).count();
+        let remove_missing = job.remove_missing && unresolved == 0;
+        if job.remove_missing && !remove_missing {
+            warn!("Not removing any tracks from the playlist, as {} tracks failed to resolve. Resume the job to retry them", unresolved);
+        }
+
+        debug!("Updating YouTube playlist {}", playlist_id);
+        let operations = sync::plan(&existing, &desired_videos, remove_missing, job.dedup == Dedup::Existing);
+        warn_quota(operations.len() as u64 * quota::WRITE_COST);
+
+        // Tracks which don't need inserting are in the playlist already
+        for (n, &i) in desired.iter().enumerate() {
+            if !operations.iter().any(|op| matches!(op, Operation::Insert { index, .. } if *index == n)) {
+                job.tracks[i].status = Status::Inserted;
+            }
+        }
+        job.save()?;
+
+        // Removals are planned before every insert and move
+        let mut removed = HashSet::new();
+        let mut done = 0;
+        let mut failures = 0;
+        for op in &operations {
+            if let Operation::Remove { item_id, video_id } = op {
+                debug!("Removing track {} from playlist", video_id);
+                done += 1;
+
+                match youtube::delete_playlist_item(item_id, auth) {
+                    Ok(_) => {
+                        removed.insert(item_id.as_str());
+                    },
+                    Err(e) => {
+                        warn!("Failed to remove track {} from the playlist: {}", video_id, e);
+                        failures += 1;
+
+                        if e.is_fatal() {
+                            error!("Stopping, {} of {} playlist changes were not made", operations.len() - done, operations.len());
+                            return Ok(failures);
+                        }
+                    }
+                }
+            }
+        }
+
+        // An item which failed to be removed shifts every later position, so the rest is planned for the items actually left.
+        // When every removal succeeded, this plans the same inserts and moves as before
+        let remaining: Vec<_> = existing.into_iter()
+            .filter(|e| !removed.contains(e.item_id.as_str()))
+            .collect();
+        let operations = sync::plan(&remaining, &desired_videos, false, false);
+        let total = done + operations.len();
+
+        // Positions are planned assuming every insert succeeds, so later positions shift down for every failed insert before them
+        let mut failed_inserts: Vec<usize> = Vec::new();
+        for op in operations {
+            done += 1;
+            // An item left in place shifts every later position, so a failed move stops the sync
+            let is_move = matches!(op, Operation::Move { .. });
+            let (video_id, result) = match op {
+                Operation::Insert { video_id, position, index } => {
+                    let adjusted = position - failed_inserts.iter().filter(|&&p| p < position).count();
+                    debug!("Adding track {} to playlist at position {}", video_id, adjusted);
+                    let result = youtube::insert_track(playlist_id, &video_id, Some(adjusted), auth);
+
+                    let t = &mut job.tracks[desired[index]];
+                    match &result {
+                        Ok(_) => t.status = Status::Inserted,
+                        Err(e) => {
+                            failed_inserts.push(position);
+                            t.status = Status::Failed;
+                            t.error = Some(e.to_string());
+                        }
+                    }
+
+                    job.save()?;
+                    (video_id, result)
+                },
+                Operation::Move { item_id, video_id, position } => {
+                    let adjusted = position - failed_inserts.iter().filter(|&&p| p < position).count();
+                    debug!("Moving track {} to position {}", video_id, adjusted);
+                    let result = youtube::move_playlist_item(playlist_id, &item_id, &video_id, adjusted, auth);
+                    (video_id, result)
+                },
+                Operation::Remove { .. } => unreachable!("Removals planned without removing")
+            };
+
+            if let Err(e) = result {
+                warn!("Failed to update track {} in the playlist: {}", video_id, e);
+                failures += 1;
+
+                if e.is_fatal() || is_move {
+                    error!("Stopping, {} of {} playlist changes were not made", total - done, total);
+                    break;
+                }
+            }
+        }
+
+        Ok(failures)
+    }
+}
+
+/// Writes to YouTube Music playlists through the internal API of the web client.
+/// Not subject to quota, but only supports appending tracks
+pub struct InnerTubeSink {
+    pub session: ytmusic::Session,
+    /// Number of tracks to add per request
+    pub batch_size: usize
+}
+
+impl PlaylistSink for InnerTubeSink {
+    fn name(&self) -> &'static str {
+        "YouTube Music"
+    }
+
+    fn catalog(&self) -> &dyn Catalog {
+        &YouTubeMusic
+    }
+
+    fn create_playlist(&self, details: &PlaylistDetails) -> Result<String> {
+        ytmusic::create_playlist(&self.session, details)
+    }
+
+    /// Appends the matched tracks. Videos already in the playlist are skipped when deduplicating, and when adding to an existing playlist,
+    /// which would otherwise get every track again
+    fn add_items(&self, job: &mut Job, playlist_id: &str, created: bool) -> Result<usize> {
+        if job.remove_missing {
+            warn!("Removing tracks is not supported through YouTube Music, only missing tracks will be added");
+        }
+        if job.dedup == Dedup::Existing {
+            warn!("Removing repeated tracks is not supported through YouTube Music, only tracks which aren't in the playlist yet will be added");
+        }
+
+        let skip_existing = job.dedup != Dedup::None || !created;
+        if skip_existing && job.dedup == Dedup::None {
+            // YouTube Music would leave out repeated tracks silently, so they are marked as such
+            warn!("Adding to an existing playlist through YouTube Music, every video is only added once");
+            dedup::mark_duplicates(job);
+        }
+
+        service::append_in_batches(job, self.batch_size, |ids| ytmusic::add_tracks(&self.session, playlist_id, ids, skip_existing))
+    }
+}
+
+/// Warn the user if `cost` quota units exceed what's left of today's budget
+fn warn_quota(cost: u64) {
+    if let Some(remaining) = quota::remaining() {
+        if cost > remaining {
+            warn!("This conversion needs up to {} YouTube quota units, but only {} are left today. It will stop after {} tracks, resume it once the quota resets", cost, remaining, remaining / quota::WRITE_COST);
+        }
+    }
+}
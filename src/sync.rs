@@ -0,0 +1,333 @@
+use std::collections::{HashMap, HashSet};
+use crate::api::youtube::PlaylistEntry;
+
+/// A change to make to a YouTube playlist. Operations must be applied in order, as positions depend on the preceding operations
+#[derive(Debug)]
+pub enum Operation {
+    /// Insert a video at the given position
+    Insert {
+        video_id: String,
+        position: usize,
+        /// Index of the video in the desired playlist
+        index: usize
+    },
+    /// Move an item to the given position
+    Move {
+        item_id: String,
+        video_id: String,
+        position: usize
+    },
+    /// Remove an item from the playlist
+    Remove {
+        item_id: String,
+        video_id: String
+    }
+}
+
+/// Identifies the n-th occurrence of a video, so playlists containing a video more than once can be compared
+type Key = (String, usize);
+
+/// An item in the playlist as it will be after applying the operations planned so far
+struct Slot {
+    /// `None` for items which aren't in the desired playlist, but which we're not removing
+    key: Option<Key>,
+    item_id: Option<String>
+}
+
+/// Compute the operations needed to turn `existing` into `desired`, in the same order.
+/// Missing videos are inserted at their position, and the fewest possible items are moved to fix the order of the rest.
+/// If `remove_missing` is set, items in `existing` which aren't in `desired` are removed, otherwise they are left in place.
+/// If `remove_duplicates` is set, repeated occurrences of a video in `existing` which aren't in `desired` are removed as well.
+/// Removals come before every other operation, so if one fails, the rest can be planned again for the items left
+pub fn plan(existing: &[PlaylistEntry], desired: &[String], remove_missing: bool, remove_duplicates: bool) -> Vec<Operation> {
+    let desired = keys(desired.iter().map(String::as_str));
+    let desired_index: HashMap<&Key, usize> = desired.iter()
+        .enumerate()
+        .map(|(i, k)| (k, i))
+        .collect();
+
+    let mut operations = Vec::new();
+    let mut slots = Vec::new();
+    for (entry, key) in existing.iter().zip(keys(existing.iter().map(|e| e.video_id.as_str()))) {
+        if desired_index.contains_key(&key) {
+            slots.push(Slot {
+                key: Some(key),
+                item_id: Some(entry.item_id.clone())
+            });
+        } else if remove_missing || (remove_duplicates && key.1 > 1) {
+            operations.push(Operation::Remove {
+                item_id: entry.item_id.clone(),
+                video_id: entry.video_id.clone()
+            });
+        } else {
+            slots.push(Slot {
+                key: None,
+                item_id: Some(entry.item_id.clone())
+            });
+        }
+    }
+
+    // The longest run of items which are already in the right order relative to each other can stay where they are
+    let present: Vec<usize> = slots.iter()
+        .filter_map(|s| s.key.as_ref())
+        .map(|k| desired_index[k])
+        .collect();
+    let stable: HashSet<usize> = longest_increasing_subsequence(&present).into_iter().collect();
+
+    // Every other item is placed directly after its predecessor in the desired order.
+    // Items placed earlier never move again, so when we're done every item follows its predecessor
+    for (i, key) in desired.iter().enumerate() {
+        if stable.contains(&i) {
+            continue;
+        }
+
+        let current = slots.iter().position(|s| s.key.as_ref() == Some(key));
+        let item_id = current.and_then(|idx| slots.remove(idx).item_id);
+
+        let position = match i {
+            0 => 0,
+            _ => slots.iter()
+                .position(|s| s.key.as_ref() == Some(&desired[i - 1]))
+                .map(|idx| idx + 1)
+                .unwrap_or_default()
+        };
+
+        slots.insert(position, Slot {
+            key: Some(key.clone()),
+            item_id: item_id.clone()
+        });
+
+        operations.push(match item_id {
+            Some(item_id) => Operation::Move {
+                item_id,
+                video_id: key.0.clone(),
+                position
+            },
+            None => Operation::Insert {
+                video_id: key.0.clone(),
+                position,
+                index: i
+            }
+        });
+    }
+
+    operations
+}
+
+/// Number every occurrence of each video
+fn keys<'a, I: Iterator<Item = &'a str>>(video_ids: I) -> Vec<Key> {
+    let mut seen: HashMap<&str, usize> = HashMap::new();
+    video_ids
+        .map(|id| {
+            let n = seen.entry(id).or_default();
+            *n += 1;
+            (id.to_string(), *n)
+        })
+        .collect()
+}
+
+/// Find the longest strictly increasing subsequence of `values`, returning the values in it
+fn longest_increasing_subsequence(values: &[usize]) -> Vec<usize> {
+    // tails[l] is the index into `values` of the smallest value ending an increasing subsequence of length l + 1
+    let mut tails: Vec<usize> = Vec::new();
+    let mut predecessors = vec![None; values.len()];
+
+    for (i, &v) in values.iter().enumerate() {
+        let l = tails.partition_point(|&t| values[t] < v);
+        if l > 0 {
+            predecessors[i] = Some(tails[l - 1]);
+        }
+
+        if l == tails.len() {
+            tails.push(i);
+        } else {
+            tails[l] = i;
+        }
+    }
+
+    let mut subsequence = Vec::with_capacity(tails.len());
+    let mut current = tails.last().copied();
+    while let Some(i) = current {
+        subsequence.push(values[i]);
+        current = predecessors[i];
+    }
+
+    subsequence.reverse();
+    subsequence
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Playlist entries for `videos`, with item IDs `item0`, `item1`, ...
+    fn entries(videos: &[&str]) -> Vec<PlaylistEntry> {
+        videos.iter()
+            .enumerate()
+            .map(|(i, v)| PlaylistEntry {
+                item_id: format!("item{}", i),
+                video_id: v.to_string()
+            })
+            .collect()
+    }
+
+    fn desired(videos: &[&str]) -> Vec<String> {
+        videos.iter().map(|v| v.to_string()).collect()
+    }
+
+    /// Apply `operations` to `existing` like YouTube would, returning the videos of the resulting playlist
+    fn apply(existing: &[PlaylistEntry], operations: &[Operation]) -> Vec<String> {
+        let mut items: Vec<(String, String)> = existing.iter()
+            .map(|e| (e.item_id.clone(), e.video_id.clone()))
+            .collect();
+
+        for (n, op) in operations.iter().enumerate() {
+            match op {
+                Operation::Insert { video_id, position, .. } => items.insert(*position, (format!("new{}", n), video_id.clone())),
+                Operation::Move { item_id, position, .. } => {
+                    let current = items.iter().position(|(id, _)| id == item_id).expect("Moved item not in playlist");
+                    let item = items.remove(current);
+                    items.insert(*position, item);
+                },
+                Operation::Remove { item_id, .. } => items.retain(|(id, _)| id != item_id)
+            }
+        }
+
+        items.into_iter().map(|(_, video)| video).collect()
+    }
+
+    fn count(operations: &[Operation]) -> (usize, usize, usize) {
+        operations.iter().fold((0, 0, 0), |(i, m, r), op| match op {
+            Operation::Insert { .. } => (i + 1, m, r),
+            Operation::Move { .. } => (i, m + 1, r),
+            Operation::Remove { .. } => (i, m, r + 1)
+        })
+    }
+
+    #[test]
+    fn append_only() {
+        let existing = entries(&["a", "b"]);
+        let operations = plan(&existing, &desired(&["a", "b", "c", "d"]), false, false);
+
+        assert_eq!(count(&operations), (2, 0, 0));
+        assert_eq!(apply(&existing, &operations), desired(&["a", "b", "c", "d"]));
+    }
+
+    #[test]
+    fn unchanged() {
+        let existing = entries(&["a", "b", "c"]);
+        assert!(plan(&existing, &desired(&["a", "b", "c"]), true, true).is_empty());
+    }
+
+    #[test]
+    fn reverse() {
+        let existing = entries(&["a", "b", "c", "d"]);
+        let operations = plan(&existing, &desired(&["d", "c", "b", "a"]), false, false);
+
+        assert_eq!(count(&operations), (0, 3, 0));
+        assert_eq!(apply(&existing, &operations), desired(&["d", "c", "b", "a"]));
+    }
+
+    #[test]
+    fn rotate() {
+        let existing = entries(&["a", "b", "c", "d"]);
+        let operations = plan(&existing, &desired(&["b", "c", "d", "a"]), false, false);
+
+        assert_eq!(count(&operations), (0, 1, 0));
+        assert_eq!(apply(&existing, &operations), desired(&["b", "c", "d", "a"]));
+    }
+
+    #[test]
+    fn insert_and_reorder() {
+        let existing = entries(&["c", "a"]);
+        let operations = plan(&existing, &desired(&["a", "b", "c"]), false, false);
+
+        assert_eq!(count(&operations), (1, 1, 0));
+        assert_eq!(apply(&existing, &operations), desired(&["a", "b", "c"]));
+    }
+
+    #[test]
+    fn repeated_videos() {
+        let existing = entries(&["a", "b", "a"]);
+
+        let operations = plan(&existing, &desired(&["a", "a", "b"]), false, false);
+        assert_eq!(count(&operations), (0, 1, 0));
+        assert_eq!(apply(&existing, &operations), desired(&["a", "a", "b"]));
+
+        // Only the third occurrence is missing
+        let operations = plan(&existing, &desired(&["a", "b", "a", "a"]), false, false);
+        assert_eq!(count(&operations), (1, 0, 0));
+        assert_eq!(apply(&existing, &operations), desired(&["a", "b", "a", "a"]));
+    }
+
+    #[test]
+    fn keeps_items_not_desired() {
+        let existing = entries(&["x", "a", "b"]);
+        let operations = plan(&existing, &desired(&["b", "a"]), false, false);
+
+        assert_eq!(count(&operations), (0, 1, 0));
+        let result = apply(&existing, &operations);
+        assert!(result.contains(&"x".to_string()));
+        assert_eq!(result.into_iter().filter(|v| v != "x").collect::<Vec<_>>(), desired(&["b", "a"]));
+    }
+
+    #[test]
+    fn removes_missing_items() {
+        let existing = entries(&["x", "a", "y", "b"]);
+        let operations = plan(&existing, &desired(&["a", "b"]), true, false);
+
+        assert_eq!(count(&operations), (0, 0, 2));
+        assert_eq!(apply(&existing, &operations), desired(&["a", "b"]));
+    }
+
+    #[test]
+    fn removes_duplicates() {
+        let existing = entries(&["a", "b", "a", "x"]);
+
+        let operations = plan(&existing, &desired(&["a", "b"]), false, true);
+        assert_eq!(count(&operations), (0, 0, 1));
+        assert_eq!(apply(&existing, &operations), desired(&["a", "b", "x"]));
+
+        // Without removing duplicates, the second occurrence is kept like any other item which isn't desired
+        let operations = plan(&existing, &desired(&["a", "b"]), false, false);
+        assert!(operations.is_empty());
+    }
+
+    #[test]
+    fn failed_removal() {
+        let existing = entries(&["x", "c", "y", "a", "b"]);
+        let wanted = desired(&["a", "b", "c"]);
+        let operations = plan(&existing, &wanted, true, false);
+        assert_eq!(count(&operations), (0, 1, 2));
+
+        // Removing y fails, so it stays in the playlist
+        let removed: Vec<&str> = operations.iter()
+            .filter_map(|op| match op {
+                Operation::Remove { item_id, video_id } if video_id != "y" => Some(item_id.as_str()),
+                _ => None
+            })
+            .collect();
+        let remaining: Vec<PlaylistEntry> = existing.iter()
+            .filter(|e| !removed.contains(&e.item_id.as_str()))
+            .cloned()
+            .collect();
+
+        // The planned moves assume y is gone and put c in the wrong place
+        let moves: Vec<Operation> = operations.into_iter()
+            .filter(|op| !matches!(op, Operation::Remove { .. }))
+            .collect();
+        assert_eq!(apply(&remaining, &moves), desired(&["y", "a", "c", "b"]));
+
+        // Planning again for the items left keeps y and gets the order right
+        let operations = plan(&remaining, &wanted, false, false);
+        assert_eq!(count(&operations), (0, 1, 0));
+        assert_eq!(apply(&remaining, &operations), desired(&["y", "a", "b", "c"]));
+    }
+
+    #[test]
+    fn longest_increasing() {
+        assert_eq!(longest_increasing_subsequence(&[3, 0, 1, 4, 2, 5]), vec![0, 1, 2, 5]);
+        assert_eq!(longest_increasing_subsequence(&[3, 2, 1]).len(), 1);
+        assert!(longest_increasing_subsequence(&[]).is_empty());
+    }
+}